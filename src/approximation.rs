@@ -1,3 +1,7 @@
 mod accurate_polygon;
+mod simplification;
+mod offsetting;
 
 pub use accurate_polygon::to_accurate_polygon;
+pub use simplification::{simplify_douglas_peucker, simplify_visvalingam_whyatt};
+pub use offsetting::{LineJoin, offset_polygons};
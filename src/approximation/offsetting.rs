@@ -0,0 +1,583 @@
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::PI;
+use euclid::default::{Box2D, Point2D, Vector2D};
+use crate::more_itertools::MoreIterTools;
+use crate::geometry::{Polygon, Polygonlike};
+
+/// The shape of the offset outline at the corners
+/// where the offset edges move apart from each other.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineJoin {
+    /// The offset edges are extended until they meet.
+    /// If the miter point is farther from the corner than `limit` times the offset distance,
+    /// the corner is beveled instead.
+    Miter { limit: f64 },
+    
+    /// The offset edges are joined by a circular arc around the corner,
+    /// approximated by a polyline deviating from it by at most `tolerance`.
+    /// A zero, negative or tiny `tolerance` gives at most one vertex per degree of the arc.
+    Round { tolerance: f64 },
+    
+    /// The ends of the offset edges are joined by a straight line.
+    Bevel,
+}
+
+/// Offsets the outlines of the region enclosed by `polygons` by `distance`:
+/// outwards if it is positive and inwards if it is negative.
+/// 
+/// The outer polygons should be clockwise (with the y axis pointing down)
+/// and their holes anticlockwise, as the approximated contours are,
+/// and the region is the set of points with a positive winding number.
+/// So, the holes shrink when the outer polygons grow, and vice versa.
+/// 
+/// The result is given in the same form. The parts that vanish are removed,
+/// the ones that overlap are merged, and the holes appearing
+/// when parts merge around an empty area are added as anticlockwise polygons.
+pub fn offset_polygons(polygons: &[Polygon<f64>], distance: f64, line_join: LineJoin) -> Vec<Polygon<f64>> {
+    let rings: Vec<_> = polygons.iter()
+        .map(|polygon| offset_ring(&without_repeated_vertices(polygon), distance, line_join))
+        .collect();
+    let edges: Vec<_> = rings.iter()
+        .flat_map(|ring| ring.iter().copied().circular_pairs())
+        .filter(|(p0, p1)| p0 != p1)
+        .collect();
+    let pieces = split_at_intersections(&edges);
+    let edge_strips = EdgeStrips::new(&edges);
+    
+    // The pieces separating the inside from the outside are the boundary of the result,
+    // directed so that the inside is on the right, like the edges of a clockwise polygon.
+    // Where the edges of different rings overlap, the same piece is found several times
+    let mut found = HashSet::new();
+    let boundary: Vec<_> = pieces.into_iter()
+        .filter_map(|(p0, p1)| {
+            let normal = Vector2D::new(-(p1 - p0).y, (p1 - p0).x).try_normalize()?;
+            let midpoint = p0.lerp(p1, 0.5);
+            let step = normal * (1e-4 * (p1 - p0).length()).max(1e-9 * (midpoint.x.abs() + midpoint.y.abs() + 1.0));
+            let is_inside_on_right = edge_strips.winding_number(midpoint + step) > 0;
+            let is_inside_on_left = edge_strips.winding_number(midpoint - step) > 0;
+            match (is_inside_on_left, is_inside_on_right) {
+                (false, true) => Some((p0, p1)),
+                (true, false) => Some((p1, p0)),
+                _ => None,
+            }
+        })
+        .filter(|&(p0, p1)| found.insert([p0.x, p0.y, p1.x, p1.y].map(f64::to_bits)))
+        .collect();
+    
+    link_rings(&boundary).into_iter()
+        .map(without_collinear_vertices)
+        .filter(|ring| ring.len() >= 3)
+        .map(Polygon::from)
+        .filter(|polygon| polygon.signed_area().abs() > 1e-9)
+        .collect()
+}
+
+/// Moves each edge of a `ring` by `distance` outwards,
+/// joining the moved edges in the corners where they move apart by the `line_join`
+/// and via the original vertex where they overlap.
+/// 
+/// The result may intersect itself.
+/// The loops appearing in the corners and in the parts too thin for an inward offset
+/// have a zero or negative winding number, so they are removed by the positive fill rule.
+fn offset_ring(ring: &[Point2D<f64>], distance: f64, line_join: LineJoin) -> Vec<Point2D<f64>> {
+    if ring.len() < 3 || distance == 0.0 {
+        return ring.to_vec();
+    }
+    let normals: Vec<_> = ring.iter().copied().circular_pairs()
+        .map(|(p0, p1)| {
+            let direction = (p1 - p0).normalize();
+            Vector2D::new(direction.y, -direction.x)
+        })
+        .collect();
+    
+    let mut offset = Vec::new();
+    for (index, &vertex) in ring.iter().enumerate() {
+        let (normal0, normal1) = (normals[(index + ring.len() - 1) % ring.len()], normals[index]);
+        let (start, end) = (vertex + normal0 * distance, vertex + normal1 * distance);
+        let (cross, dot) = (normal0.cross(normal1), normal0.dot(normal1));
+        offset.push(start);
+        if cross.abs() < 1e-12 && dot > 0.0 {
+            // The edges are collinear
+        } else if cross.abs() >= 1e-12 && cross * distance < 0.0 {
+            offset.push(vertex);
+        } else {
+            // The turn from one normal to the other has the same direction as the offset,
+            // a full turn for a spike is made around its tip
+            let angle = cross.abs().atan2(dot).copysign(distance);
+            add_join(&mut offset, vertex, (normal0, normal1), angle, distance, line_join);
+        }
+        offset.push(end);
+    }
+    offset.dedup();
+    offset
+}
+
+/// The smallest angle between the vertices of a round join, one degree.
+const MIN_ROUND_STEP: f64 = PI / 180.0;
+
+/// Adds the points between the ends of two offset edges meeting at a `vertex`,
+/// where the second `normal` is the first one rotated by `angle`.
+fn add_join(
+    offset: &mut Vec<Point2D<f64>>,
+    vertex: Point2D<f64>,
+    (normal0, normal1): (Vector2D<f64>, Vector2D<f64>),
+    angle: f64,
+    distance: f64,
+    line_join: LineJoin,
+) {
+    let rotated = |vector: Vector2D<f64>, angle: f64| {
+        let (sin, cos) = angle.sin_cos();
+        Vector2D::new(vector.x * cos - vector.y * sin, vector.x * sin + vector.y * cos)
+    };
+    match line_join {
+        LineJoin::Miter { limit } => {
+            // The miter point is farther from the vertex than the edge ends by 1 / cos(angle / 2),
+            // and 2 cos²(angle / 2) = 1 + cos(angle)
+            let double_square_cos = 1.0 + normal0.dot(normal1);
+            if 2.0 <= limit * limit * double_square_cos {
+                offset.push(vertex + (normal0 + normal1) * distance / double_square_cos);
+            }
+        },
+        LineJoin::Round { tolerance } => {
+            let radius = distance.abs();
+            let max_step = if tolerance < radius { 2.0 * (1.0 - tolerance / radius).acos() } else { PI };
+            let max_step = max_step.max(MIN_ROUND_STEP);
+            let step_count = (angle.abs() / max_step).ceil().max(1.0) as usize;
+            for step in 1..step_count {
+                offset.push(vertex + rotated(normal0, angle * step as f64 / step_count as f64) * distance);
+            }
+        },
+        LineJoin::Bevel => { },
+    }
+}
+
+/// Splits the `edges` at their intersections and at the vertices lying on them.
+/// 
+/// All the points closer to each other than a rounding error are snapped to one of them,
+/// so that the common points of the pieces are exactly equal and can be linked,
+/// and no tiny pieces appear between the intersections found with different edges.
+fn split_at_intersections(edges: &[(Point2D<f64>, Point2D<f64>)]) -> Vec<(Point2D<f64>, Point2D<f64>)> {
+    let mut splits: Vec<Vec<(f64, Point2D<f64>)>> = vec![Vec::new(); edges.len()];
+    let boxes: Vec<_> = edges.iter().map(|&(p0, p1)| Box2D::from_points([p0, p1])).collect();
+    
+    let mut order: Vec<_> = (0..edges.len()).collect();
+    order.sort_by(|&a, &b| boxes[a].min.x.total_cmp(&boxes[b].min.x));
+    for (position, &a) in order.iter().enumerate() {
+        for &b in order[position + 1..].iter().take_while(|&&b| boxes[b].min.x <= boxes[a].max.x) {
+            if boxes[a].min.y <= boxes[b].max.y && boxes[b].min.y <= boxes[a].max.y {
+                for (edge, parameter, point) in intersections(edges[a], edges[b]) {
+                    splits[if edge == 0 { a } else { b }].push((parameter, point));
+                }
+            }
+        }
+    }
+    
+    let extent = edges.iter()
+        .flat_map(|&(p0, p1)| [p0.x.abs(), p0.y.abs(), p1.x.abs(), p1.y.abs()])
+        .fold(0.0, f64::max);
+    let mut snapping = Snapping::new(1e-12 * (extent + 1.0));
+    // The vertices are snapped first, so that the intersections near them are moved to them
+    for &(p0, _) in edges {
+        snapping.snap(p0);
+    }
+    
+    let mut pieces = Vec::new();
+    for (&(p0, p1), mut splits) in edges.iter().zip(splits) {
+        splits.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+        let mut points: Vec<_> = [p0].into_iter()
+            .chain(splits.into_iter().map(|(_, point)| point))
+            .chain([p1])
+            .map(|point| snapping.snap(point))
+            .collect();
+        points.dedup();
+        pieces.extend(points.windows(2).map(|pair| (pair[0], pair[1])));
+    }
+    pieces
+}
+
+/// Replaces the points with the first snapped point within `tolerance` (in each coordinate),
+/// always the same for equal points.
+struct Snapping {
+    tolerance: f64,
+    snapped: HashMap<(u64, u64), Point2D<f64>>,
+    cells: HashMap<(i64, i64), Vec<Point2D<f64>>>,
+}
+
+impl Snapping {
+    fn new(tolerance: f64) -> Self {
+        Self { tolerance, snapped: HashMap::new(), cells: HashMap::new() }
+    }
+    
+    fn snap(&mut self, point: Point2D<f64>) -> Point2D<f64> {
+        let key = (point.x.to_bits(), point.y.to_bits());
+        if let Some(&snapped) = self.snapped.get(&key) {
+            return snapped;
+        }
+        // The cells are as large as the tolerance, so the near points are in the neighboring ones
+        let cell = ((point.x / self.tolerance).floor() as i64, (point.y / self.tolerance).floor() as i64);
+        let near = (-1..=1)
+            .flat_map(|dx| (-1..=1).map(move |dy| (cell.0 + dx, cell.1 + dy)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .find(|other| (other.x - point.x).abs() <= self.tolerance && (other.y - point.y).abs() <= self.tolerance)
+            .copied();
+        let snapped = near.unwrap_or_else(|| {
+            self.cells.entry(cell).or_default().push(point);
+            point
+        });
+        self.snapped.insert(key, snapped);
+        snapped
+    }
+}
+
+/// The points where one of two edges `a` and `b` should be split,
+/// as the index of the edge (0 or 1), the parameter along it, and the point itself.
+/// 
+/// If a vertex of one edge lies on the other one, the vertex itself is used as the point.
+fn intersections(
+    (a0, a1): (Point2D<f64>, Point2D<f64>),
+    (b0, b1): (Point2D<f64>, Point2D<f64>),
+) -> Vec<(usize, f64, Point2D<f64>)> {
+    let (a, b) = (a1 - a0, b1 - b0);
+    let denominator = a.cross(b);
+    let mut result = Vec::new();
+    if denominator == 0.0 {
+        if (b0 - a0).cross(a) == 0.0 {
+            // Collinear edges are split at the vertices of each other within them
+            for (edge, (p0, direction), others) in [(0, (a0, a), [b0, b1]), (1, (b0, b), [a0, a1])] {
+                for point in others {
+                    let parameter = (point - p0).dot(direction) / direction.square_length();
+                    if 0.0 < parameter && parameter < 1.0 {
+                        result.push((edge, parameter, point));
+                    }
+                }
+            }
+        }
+        return result;
+    }
+    
+    let (t, u) = ((b0 - a0).cross(b) / denominator, (b0 - a0).cross(a) / denominator);
+    if !(0.0..=1.0).contains(&t) || !(0.0..=1.0).contains(&u) {
+        return result;
+    }
+    let point = match (t, u) {
+        (_, 0.0) => b0,
+        (_, 1.0) => b1,
+        (0.0, _) => a0,
+        (1.0, _) => a1,
+        _ => a0 + a * t,
+    };
+    if 0.0 < t && t < 1.0 {
+        result.push((0, t, point));
+    }
+    if 0.0 < u && u < 1.0 {
+        result.push((1, u, point));
+    }
+    result
+}
+
+/// The edges of closed rings grouped into horizontal strips
+/// for computing winding numbers without checking all the edges.
+struct EdgeStrips<'a> {
+    edges: &'a [(Point2D<f64>, Point2D<f64>)],
+    top: f64,
+    strip_height: f64,
+    strips: Vec<Vec<usize>>,
+}
+
+impl<'a> EdgeStrips<'a> {
+    fn new(edges: &'a [(Point2D<f64>, Point2D<f64>)]) -> Self {
+        let (top, bottom) = edges.iter()
+            .flat_map(|&(p0, p1)| [p0.y, p1.y])
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(top, bottom), y| (top.min(y), bottom.max(y)));
+        let strip_count = (edges.len() as f64).sqrt().ceil().max(1.0) as usize;
+        let strip_height = ((bottom - top) / strip_count as f64).max(f64::MIN_POSITIVE);
+        let mut this = Self { edges, top, strip_height, strips: vec![Vec::new(); strip_count] };
+        for (index, &(p0, p1)) in edges.iter().enumerate() {
+            for strip in this.strip_index(p0.y.min(p1.y))..=this.strip_index(p0.y.max(p1.y)) {
+                this.strips[strip].push(index);
+            }
+        }
+        this
+    }
+    
+    fn strip_index(&self, y: f64) -> usize {
+        (((y - self.top) / self.strip_height).floor().max(0.0) as usize).min(self.strips.len() - 1)
+    }
+    
+    /// Winding number of a `point` with respect to the rings.
+    /// See `Polygonlike::winding_number`.
+    fn winding_number(&self, point: Point2D<f64>) -> i32 {
+        let mut winding_number = 0;
+        for &(p0, p1) in self.strips[self.strip_index(point.y)].iter().map(|&index| &self.edges[index]) {
+            let side = (p1 - p0).cross(point - p0);
+            if p0.y <= point.y && point.y < p1.y && side > 0.0 {
+                winding_number += 1;
+            } else if p1.y <= point.y && point.y < p0.y && side < 0.0 {
+                winding_number -= 1;
+            }
+        }
+        winding_number
+    }
+}
+
+/// Links directed `edges` into closed rings.
+/// 
+/// Where several edges start at the same point, the one turning most clockwise is taken,
+/// so that the parts touching each other at a point make separate rings.
+/// A chain that cannot be closed, which only a misclassified piece can cause, is dropped.
+fn link_rings(edges: &[(Point2D<f64>, Point2D<f64>)]) -> Vec<Vec<Point2D<f64>>> {
+    let key = |point: Point2D<f64>| (point.x.to_bits(), point.y.to_bits());
+    let mut outgoing: HashMap<_, Vec<usize>> = HashMap::new();
+    for (index, &(p0, _)) in edges.iter().enumerate() {
+        outgoing.entry(key(p0)).or_default().push(index);
+    }
+    
+    let mut is_used = vec![false; edges.len()];
+    let mut rings = Vec::new();
+    for first in 0..edges.len() {
+        if is_used[first] {
+            continue;
+        }
+        let mut ring = Vec::new();
+        let mut current = first;
+        loop {
+            is_used[current] = true;
+            let (p0, p1) = edges[current];
+            ring.push(p0);
+            if p1 == edges[first].0 {
+                rings.push(ring);
+                break;
+            }
+            let direction = p1 - p0;
+            let next = outgoing.get(&key(p1))
+                .into_iter()
+                .flatten()
+                .copied()
+                .filter(|&index| !is_used[index])
+                .max_by(|&a, &b| {
+                    let turn = |index: usize| {
+                        let next_direction = edges[index].1 - edges[index].0;
+                        direction.cross(next_direction).atan2(direction.dot(next_direction))
+                    };
+                    turn(a).total_cmp(&turn(b))
+                });
+            let Some(next) = next else { break };
+            current = next;
+        }
+    }
+    rings
+}
+
+fn without_repeated_vertices(polygon: &Polygon<f64>) -> Vec<Point2D<f64>> {
+    let mut vertices: Vec<_> = polygon.vertices().collect();
+    vertices.dedup();
+    while vertices.len() > 1 && vertices.first() == vertices.last() {
+        vertices.pop();
+    }
+    vertices
+}
+
+/// Removes the vertices in the middle of straight lines,
+/// which appear where the edges are split.
+fn without_collinear_vertices(ring: Vec<Point2D<f64>>) -> Vec<Point2D<f64>> {
+    let len = ring.len();
+    (0..len)
+        .filter(|&index| {
+            let (previous, vertex, next) = (ring[(index + len - 1) % len], ring[index], ring[(index + 1) % len]);
+            let (incoming, outgoing) = (vertex - previous, next - vertex);
+            incoming.cross(outgoing).abs() > 1e-12 * incoming.length() * outgoing.length() || incoming.dot(outgoing) < 0.0
+        })
+        .map(|index| ring[index])
+        .collect()
+}
+
+
+// ---------
+
+#[cfg(test)]
+mod tests {
+    use euclid::point2;
+    use test_case::test_case;
+    use crate::geometry::Orientation;
+    use crate::image_contour_collection::{ImageContourCollection, Connectivity};
+    use crate::test_images::get_test_image;
+    use crate::approximation::to_accurate_polygon;
+    use super::*;
+    
+    fn rect(x0: f64, y0: f64, x1: f64, y1: f64) -> Polygon<f64> {
+        Polygon::from(vec![point2(x0, y0), point2(x1, y0), point2(x1, y1), point2(x0, y1)])
+    }
+    
+    fn total_area(polygons: &[Polygon<f64>]) -> f64 {
+        polygons.iter().map(|polygon| polygon.signed_area()).sum()
+    }
+    
+    const MITER: LineJoin = LineJoin::Miter { limit: 2.0 };
+    const ROUND: LineJoin = LineJoin::Round { tolerance: 0.001 };
+    
+    #[test_case(MITER => 144.0)]
+    #[test_case(LineJoin::Miter { limit: 1.4 } => 142.0)]
+    #[test_case(LineJoin::Bevel => 142.0)]
+    #[test_case(ROUND => 143.14)]
+    fn test_outset(line_join: LineJoin) -> f64 {
+        let offset = offset_polygons(&[rect(0.0, 0.0, 10.0, 10.0)], 1.0, line_join);
+        assert_eq!(offset.len(), 1);
+        assert_eq!(offset[0].orientation(), Some(Orientation::Clockwise));
+        (total_area(&offset) * 100.0).round() / 100.0
+    }
+    
+    #[test]
+    fn test_miter() {
+        let offset = offset_polygons(&[rect(0.0, 0.0, 10.0, 10.0)], 1.0, MITER);
+        assert_eq!(offset[0].vertices().collect::<Vec<_>>(), [point2(-1.0, -1.0), point2(11.0, -1.0), point2(11.0, 11.0), point2(-1.0, 11.0)]);
+    }
+    
+    // The offset edges move apart in the inner corner of the L-shape
+    #[test_case(MITER => 5.0)]
+    #[test_case(LineJoin::Bevel => 5.125)]
+    #[test_case(ROUND => 5.054)]
+    fn test_inset(line_join: LineJoin) -> f64 {
+        let l_shape = Polygon::from(vec![point2(0.0, 0.0), point2(4.0, 0.0), point2(4.0, 2.0), point2(2.0, 2.0), point2(2.0, 4.0), point2(0.0, 4.0)]);
+        let offset = offset_polygons(&[l_shape], -0.5, line_join);
+        assert_eq!(offset.len(), 1);
+        (total_area(&offset) * 1000.0).round() / 1000.0
+    }
+    
+    #[test_case(MITER)]
+    #[test_case(ROUND)]
+    #[test_case(LineJoin::Bevel)]
+    fn test_vanishing(line_join: LineJoin) {
+        let polygons = [rect(0.0, 0.0, 10.0, 10.0), rect(20.0, 0.0, 22.0, 10.0)];
+        let offset = offset_polygons(&polygons, -1.5, line_join);
+        assert_eq!(offset.len(), 1);
+        assert!((total_area(&offset) - 49.0).abs() < 1e-9);
+        assert!(offset_polygons(&polygons, -6.0, line_join).is_empty());
+    }
+    
+    #[test]
+    fn test_merging() {
+        let polygons = [rect(0.0, 0.0, 10.0, 10.0), rect(11.0, 0.0, 21.0, 10.0)];
+        let offset = offset_polygons(&polygons, 1.0, MITER);
+        assert_eq!(offset.len(), 1);
+        assert_eq!(total_area(&offset), 23.0 * 12.0);
+    }
+    
+    #[test]
+    fn test_merging_around_hole() {
+        // A ring of four rects with a 2×2 gap in the middle
+        let polygons = [
+            rect(0.0, 0.0, 10.0, 4.0),
+            rect(6.0, 4.0, 10.0, 10.0),
+            rect(0.0, 6.0, 6.0, 10.0),
+            rect(0.0, 4.0, 3.0, 6.0),
+        ];
+        let offset = offset_polygons(&polygons, 0.5, MITER);
+        assert_eq!(offset.len(), 2);
+        assert_eq!(total_area(&offset), 11.0 * 11.0 - 2.0 * 1.0);
+        let hole = offset.iter().find(|polygon| polygon.orientation() == Some(Orientation::Anticlockwise)).unwrap();
+        assert_eq!(hole.bounding_box(), Box2D::new(point2(3.5, 4.5), point2(5.5, 5.5)));
+    }
+    
+    #[test_case(1.0 => (2, 140.0))]
+    #[test_case(2.5 => (1, 225.0))]
+    #[test_case(-1.0 => (2, 64.0 - 36.0))]
+    fn test_hole(distance: f64) -> (usize, f64) {
+        let polygons = [rect(0.0, 0.0, 10.0, 10.0), rect(3.0, 3.0, 7.0, 7.0).reversed()];
+        let offset = offset_polygons(&polygons, distance, MITER);
+        (offset.len(), total_area(&offset))
+    }
+    
+    #[test]
+    fn test_spike() {
+        // A zero-width spike going right from the middle of the right edge
+        let polygon = || Polygon::from(vec![point2(0.0, 0.0), point2(4.0, 0.0), point2(4.0, 2.0), point2(8.0, 2.0), point2(4.0, 2.0), point2(4.0, 4.0), point2(0.0, 4.0)]);
+        let offset = offset_polygons(&[polygon()], 1.0, LineJoin::Bevel);
+        assert_eq!(offset.len(), 1);
+        assert_eq!(offset[0].bounding_box(), Box2D::new(point2(-1.0, -1.0), point2(8.0, 5.0)));
+        assert!(offset_polygons(&[polygon()], -2.5, LineJoin::Bevel).is_empty());
+    }
+    
+    #[test_case(0.0)]
+    #[test_case(1e-300)]
+    #[test_case(-1.0)]
+    fn test_round_with_tiny_tolerance(tolerance: f64) {
+        let offset = offset_polygons(&[rect(0.0, 0.0, 10.0, 10.0)], 1.0, LineJoin::Round { tolerance });
+        assert_eq!(offset.len(), 1);
+        assert_eq!(offset[0].vertices().count(), 4 * 91);
+        assert!((total_area(&offset) - (140.0 + PI)).abs() < 1e-3);
+    }
+    
+    #[test]
+    fn test_splitting_with_rounding_error() {
+        // The edge from b to c misses the vertex c of the next one by a rounding error,
+        // and the last edge crosses the first one at a rounding error from the vertex a
+        let (a, b, c, d) = (point2(0.0, 0.0), point2(1.0, 0.0), point2(1.0, 1.0), point2(0.0, 1.0));
+        let c_rounded = point2(1.0, 1.0 + f64::EPSILON);
+        let pieces = split_at_intersections(&[(a, b), (b, c_rounded), (c, d), (d, point2(f64::EPSILON, -f64::EPSILON))]);
+        assert_eq!(pieces, [(a, b), (b, c), (c, d), (d, a)]);
+        assert_eq!(link_rings(&pieces), [vec![a, b, c, d]]);
+    }
+    
+    // The edges of the rects and the slanted polygon intersect very close to each other
+    // and to their vertices, leaving tiny pieces between the split points
+    #[test_case(1e-13)]
+    #[test_case(1e-10)]
+    #[test_case(1e-7)]
+    fn test_near_coincident_intersections(shift: f64) {
+        let slanted = Polygon::from(vec![point2(10.0 - shift, -5.0), point2(10.0 + shift, 15.0), point2(9.0, 15.0), point2(9.0, -5.0)]);
+        let polygons = [rect(0.0, 0.0, 10.0, 10.0), rect(10.0 + shift, 0.0, 20.0, 10.0 - shift), slanted];
+        for distance in [0.1, 1.0 / 3.0, 1.0] {
+            let offset = offset_polygons(&polygons, distance, MITER);
+            assert_eq!(offset.len(), 1);
+            let expected = (20.0 + 2.0 * distance) * (10.0 + 2.0 * distance) + 2.0 * (1.0 + 2.0 * distance) * 5.0;
+            assert!((total_area(&offset) - expected).abs() < 1e-4, "{} ≠ {expected}", total_area(&offset));
+        }
+    }
+    
+    fn distance_to_outline(polygons: &[Polygon<f64>], point: Point2D<f64>) -> f64 {
+        polygons.iter()
+            .flat_map(|polygon| polygon.edges())
+            .map(|(p0, p1)| {
+                let t = ((point - p0).dot(p1 - p0) / (p1 - p0).square_length()).clamp(0.0, 1.0);
+                (point - p0.lerp(p1, t)).length()
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
+    
+    fn is_inside(polygons: &[Polygon<f64>], point: Point2D<f64>) -> bool {
+        polygons.iter().map(|polygon| polygon.winding_number(point)).sum::<i32>() > 0
+    }
+    
+    /// With round joins, the offset region consists of the points
+    /// closer than `distance` to the original region (or farther from the outside for an inset).
+    #[test_case("text_142x64_theos", 1.5)]
+    #[test_case("text_142x64_theos", -0.7)]
+    #[test_case("pattern_164x164_ga", 2.0)]
+    #[test_case("pattern_164x164_ga", -1.2)]
+    fn test_offset_in_collection(name: &str, distance: f64) {
+        let image = get_test_image(name).unwrap();
+        let contour_collection = ImageContourCollection::new(&image, false, Connectivity::Eight);
+        let polygons: Vec<_> = contour_collection.all_contours().map(|contour| to_accurate_polygon(&contour)).collect();
+        let offset = offset_polygons(&polygons, distance, LineJoin::Round { tolerance: 0.01 });
+        assert!(offset.iter().all(|polygon| polygon.orientation().is_some()));
+        
+        let (width, height) = image.dimensions();
+        for y in (0..height).step_by(2) {
+            for x in 0..width {
+                let point = point2(x as f64 + 0.1, y as f64 + 0.7);
+                let outline_distance = distance_to_outline(&polygons, point);
+                if (outline_distance - distance.abs()).abs() < 0.02 {
+                    continue;
+                }
+                let is_originally_inside = is_inside(&polygons, point);
+                let expected = if distance > 0.0 {
+                    is_originally_inside || outline_distance < distance
+                } else {
+                    is_originally_inside && outline_distance > -distance
+                };
+                assert_eq!(is_inside(&offset, point), expected, "{name}: {point:?}");
+            }
+        }
+    }
+}
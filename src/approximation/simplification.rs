@@ -0,0 +1,368 @@
+use std::collections::BTreeSet;
+use euclid::default::{Box2D, Point2D};
+use crate::geometry::{FillRule, Polygon, Polygonlike};
+
+/// Simplifies `polygons` by the Douglas-Peucker algorithm,
+/// removing the vertices that lie closer than `tolerance`
+/// to the edge replacing them. Each polygon keeps at least three vertices.
+/// 
+/// If `preserve_topology` is `true`, the polygons are simplified together,
+/// e. g. a contour with its holes or all the contours of a collection:
+/// some of the removed vertices are restored, so that no edges of the results
+/// cross each other and no polygon moves to the other side of an edge.
+/// Thus, holes stay inside their outer contours, and siblings never intersect,
+/// provided that the original polygons do not intersect.
+pub fn simplify_douglas_peucker(polygons: &[Polygon<f64>], tolerance: f64, preserve_topology: bool) -> Vec<Polygon<f64>> {
+    simplify(polygons, tolerance, preserve_topology, douglas_peucker_significance)
+}
+
+/// Simplifies `polygons` by the Visvalingam-Whyatt algorithm,
+/// repeatedly removing the vertex forming the smallest triangle with its neighbors
+/// while the area of the triangle does not exceed `area_tolerance`.
+/// Each polygon keeps at least three vertices.
+/// 
+/// See `simplify_douglas_peucker` for `preserve_topology`.
+pub fn simplify_visvalingam_whyatt(polygons: &[Polygon<f64>], area_tolerance: f64, preserve_topology: bool) -> Vec<Polygon<f64>> {
+    simplify(polygons, area_tolerance, preserve_topology, visvalingam_whyatt_significance)
+}
+
+/// Keeps the vertices with significance greater than `tolerance`
+/// (and, at least, three most significant ones) and then, if needed, restores the topology.
+fn simplify(
+    polygons: &[Polygon<f64>],
+    tolerance: f64,
+    preserve_topology: bool,
+    get_significance: impl Fn(&[Point2D<f64>]) -> Vec<f64>,
+) -> Vec<Polygon<f64>> {
+    let rings: Vec<Vec<_>> = polygons.iter().map(|polygon| polygon.vertices().collect()).collect();
+    let significances: Vec<_> = rings.iter().map(|ring| get_significance(ring)).collect();
+    let mut kept: Vec<_> = significances.iter().map(|significance| keep(significance, tolerance)).collect();
+    if preserve_topology {
+        restore_topology(&rings, &significances, &mut kept);
+    }
+    
+    rings.iter().zip(kept)
+        .map(|(ring, kept)| Polygon::new(ring.iter().zip(kept).filter(|&(_, is_kept)| is_kept).map(|(&vertex, _)| vertex)))
+        .collect()
+}
+
+fn keep(significance: &[f64], tolerance: f64) -> Vec<bool> {
+    let mut kept: Vec<_> = significance.iter().map(|&value| value > tolerance).collect();
+    let mut indices: Vec<_> = (0..significance.len()).collect();
+    indices.sort_by(|&a, &b| significance[b].total_cmp(&significance[a]));
+    for &index in indices.iter().take(3) {
+        kept[index] = true;
+    }
+    kept
+}
+
+/// For each vertex, the greatest tolerance at which the Douglas-Peucker algorithm removes it,
+/// i. e. its distance to the edge replacing it, but not greater than the one of its parent.
+fn douglas_peucker_significance(ring: &[Point2D<f64>]) -> Vec<f64> {
+    let len = ring.len();
+    let mut significance = vec![f64::INFINITY; len];
+    if len <= 3 {
+        return significance;
+    }
+    
+    // The ring is split in two chains by the vertex farthest from the first one
+    let farthest = (1..len)
+        .max_by(|&a, &b| (ring[a] - ring[0]).square_length().total_cmp(&(ring[b] - ring[0]).square_length()))
+        .unwrap();
+    // Chain ends are unwrapped indices, `len` stands for 0
+    let mut stack = vec![(0, farthest, f64::INFINITY), (farthest, len, f64::INFINITY)];
+    while let Some((start, end, parent_significance)) = stack.pop() {
+        let (p0, p1) = (ring[start], ring[end % len]);
+        let farthest = (start + 1..end)
+            .map(|index| (index, segment_distance(ring[index], p0, p1)))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+        if let Some((index, distance)) = farthest {
+            significance[index] = distance.min(parent_significance);
+            stack.push((start, index, significance[index]));
+            stack.push((index, end, significance[index]));
+        }
+    }
+    significance
+}
+
+/// For each vertex, the effective area at which the Visvalingam-Whyatt algorithm removes it,
+/// i. e. the area of the triangle it forms with its neighbors at the time of removal,
+/// but not less than the effective area of any vertex removed before.
+fn visvalingam_whyatt_significance(ring: &[Point2D<f64>]) -> Vec<f64> {
+    let len = ring.len();
+    let mut significance = vec![f64::INFINITY; len];
+    if len <= 3 {
+        return significance;
+    }
+    
+    let mut previous: Vec<_> = (0..len).map(|index| (index + len - 1) % len).collect();
+    let mut next: Vec<_> = (0..len).map(|index| (index + 1) % len).collect();
+    let triangle_area = |index: usize, previous: &[usize], next: &[usize]| {
+        let (p0, p1, p2) = (ring[previous[index]], ring[index], ring[next[index]]);
+        (p1 - p0).cross(p2 - p0).abs() / 2.0
+    };
+    let mut areas: Vec<_> = (0..len).map(|index| triangle_area(index, &previous, &next)).collect();
+    // Non-negative floats are ordered as their bit patterns
+    let mut queue: BTreeSet<_> = (0..len).map(|index| (areas[index].to_bits(), index)).collect();
+    
+    let mut effective_area: f64 = 0.0;
+    for _ in 3..len {
+        let (_, index) = queue.pop_first().unwrap();
+        effective_area = effective_area.max(areas[index]);
+        significance[index] = effective_area;
+        
+        let (p, n) = (previous[index], next[index]);
+        next[p] = n;
+        previous[n] = p;
+        for neighbor in [p, n] {
+            queue.remove(&(areas[neighbor].to_bits(), neighbor));
+            areas[neighbor] = triangle_area(neighbor, &previous, &next);
+            queue.insert((areas[neighbor].to_bits(), neighbor));
+        }
+    }
+    significance
+}
+
+/// Edge of a simplified polygon replacing a chain of the original one.
+struct Edge {
+    ring: usize,
+    start: usize,
+    /// Unwrapped index of the end vertex, i. e. possibly greater than the ring length.
+    end: usize,
+    bounding_box: Box2D<f64>,
+}
+
+/// Restores the most significant removed vertex of each edge
+/// crossing another edge or having a vertex of another edge
+/// between itself and the chain it replaces, until there are no such edges
+/// (or they have no vertices to restore).
+fn restore_topology(rings: &[Vec<Point2D<f64>>], significances: &[Vec<f64>], kept: &mut [Vec<bool>]) {
+    loop {
+        let edges = get_edges(rings, kept);
+        let mut is_changed = false;
+        for edge in find_bad_edges(rings, kept, &edges) {
+            let len = rings[edge.ring].len();
+            let most_significant = (edge.start + 1..edge.end)
+                .map(|index| index % len)
+                .max_by(|&a, &b| significances[edge.ring][a].total_cmp(&significances[edge.ring][b]));
+            if let Some(index) = most_significant {
+                kept[edge.ring][index] = true;
+                is_changed = true;
+            }
+        }
+        if !is_changed {
+            break;
+        }
+    }
+}
+
+fn get_edges(rings: &[Vec<Point2D<f64>>], kept: &[Vec<bool>]) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    for (ring_index, ring) in rings.iter().enumerate() {
+        let kept_indices: Vec<_> = (0..ring.len()).filter(|&index| kept[ring_index][index]).collect();
+        for (position, &start) in kept_indices.iter().enumerate() {
+            let end = kept_indices.get(position + 1).copied().unwrap_or(kept_indices[0] + ring.len());
+            let bounding_box = Box2D::from_points([ring[start], ring[end % ring.len()]]);
+            edges.push(Edge { ring: ring_index, start, end, bounding_box });
+        }
+    }
+    edges
+}
+
+fn find_bad_edges<'a>(rings: &[Vec<Point2D<f64>>], kept: &[Vec<bool>], edges: &'a [Edge]) -> Vec<&'a Edge> {
+    let endpoints = |edge: &Edge| {
+        let ring = &rings[edge.ring];
+        (ring[edge.start], ring[edge.end % ring.len()])
+    };
+    let mut is_bad = vec![false; edges.len()];
+    
+    // Crossing edges are found by sweeping along the x axis
+    let mut order: Vec<_> = (0..edges.len()).collect();
+    order.sort_by(|&a, &b| edges[a].bounding_box.min.x.total_cmp(&edges[b].bounding_box.min.x));
+    for (position, &a) in order.iter().enumerate() {
+        for &b in order[position + 1..].iter().take_while(|&&b| edges[b].bounding_box.min.x <= edges[a].bounding_box.max.x) {
+            let (edge_a, edge_b) = (&edges[a], &edges[b]);
+            let len = rings[edge_a.ring].len();
+            let are_adjacent = edge_a.ring == edge_b.ring
+                && (edge_a.end % len == edge_b.start || edge_b.end % len == edge_a.start);
+            let overlap_y = edge_a.bounding_box.min.y <= edge_b.bounding_box.max.y
+                && edge_b.bounding_box.min.y <= edge_a.bounding_box.max.y;
+            if !are_adjacent && overlap_y {
+                let ((a0, a1), (b0, b1)) = (endpoints(edge_a), endpoints(edge_b));
+                if segments_intersect(a0, a1, b0, b1) {
+                    is_bad[a] = true;
+                    is_bad[b] = true;
+                }
+            }
+        }
+    }
+    
+    // A vertex between an edge and its chain means that a part of a polygon has gone over the edge
+    let mut vertices: Vec<_> = rings.iter().enumerate()
+        .flat_map(|(ring_index, ring)| (0..ring.len())
+            .filter(move |&index| kept[ring_index][index])
+            .map(move |index| (ring[index], ring_index, index)))
+        .collect();
+    vertices.sort_by(|(a, _, _), (b, _, _)| a.x.total_cmp(&b.x));
+    for (edge_index, edge) in edges.iter().enumerate() {
+        if is_bad[edge_index] || edge.end - edge.start < 2 {
+            continue;
+        }
+        let ring = &rings[edge.ring];
+        let chain = Polygon::new((edge.start..=edge.end).map(|index| ring[index % ring.len()]));
+        let chain_box = chain.bounding_box();
+        let first = vertices.partition_point(|(vertex, _, _)| vertex.x < chain_box.min.x);
+        is_bad[edge_index] = vertices[first..].iter()
+            .take_while(|(vertex, _, _)| vertex.x <= chain_box.max.x)
+            .filter(|&&(_, ring_index, index)|
+                ring_index != edge.ring || (index + ring.len() - edge.start) % ring.len() > edge.end - edge.start)
+            .any(|&(vertex, _, _)| chain_box.contains_inclusive(vertex) && chain.contains(vertex, FillRule::NonZero));
+    }
+    
+    edges.iter().zip(is_bad).filter(|&(_, is_bad)| is_bad).map(|(edge, _)| edge).collect()
+}
+
+fn segment_distance(point: Point2D<f64>, p0: Point2D<f64>, p1: Point2D<f64>) -> f64 {
+    let segment = p1 - p0;
+    let square_length = segment.square_length();
+    if square_length == 0.0 {
+        return (point - p0).length();
+    }
+    let t = ((point - p0).dot(segment) / square_length).clamp(0.0, 1.0);
+    (point - (p0 + segment * t)).length()
+}
+
+/// Checks if two segments have at least one common point.
+fn segments_intersect(a0: Point2D<f64>, a1: Point2D<f64>, b0: Point2D<f64>, b1: Point2D<f64>) -> bool {
+    let side = |p0: Point2D<f64>, p1: Point2D<f64>, point: Point2D<f64>| (p1 - p0).cross(point - p0);
+    let is_within = |p0: Point2D<f64>, p1: Point2D<f64>, point: Point2D<f64>|
+        Box2D::from_points([p0, p1]).contains_inclusive(point);
+    
+    let (d0, d1) = (side(b0, b1, a0), side(b0, b1, a1));
+    let (d2, d3) = (side(a0, a1, b0), side(a0, a1, b1));
+    if d0 * d1 < 0.0 && d2 * d3 < 0.0 {
+        return true;
+    }
+    (d0 == 0.0 && is_within(b0, b1, a0)) || (d1 == 0.0 && is_within(b0, b1, a1))
+        || (d2 == 0.0 && is_within(a0, a1, b0)) || (d3 == 0.0 && is_within(a0, a1, b1))
+}
+
+
+// ---------
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use euclid::point2;
+    use test_case::test_case;
+    use crate::geometry::Orientation;
+    use crate::image_contour_collection::{ImageContourCollection, Connectivity};
+    use crate::test_images::get_test_image;
+    use crate::approximation::to_accurate_polygon;
+    use super::*;
+    
+    type Simplifier = fn(&[Polygon<f64>], f64, bool) -> Vec<Polygon<f64>>;
+    
+    /// A square with a bump on the bottom edge and noise on the other edges.
+    fn bumpy_square() -> Polygon<f64> {
+        Polygon::from(vec![
+            point2(0.0, 0.0), point2(5.0, 0.2), point2(10.0, 0.0), point2(9.9, 5.0), point2(10.0, 10.0),
+            point2(6.0, 10.0), point2(5.0, 11.0), point2(4.0, 10.0), point2(0.0, 10.0), point2(0.1, 5.0),
+        ])
+    }
+    
+    /// A small hole inside the bump of `bumpy_square`.
+    fn hole_in_bump() -> Polygon<f64> {
+        Polygon::from(vec![point2(4.8, 10.2), point2(5.0, 10.6), point2(5.2, 10.2)])
+    }
+    
+    fn vertices(polygon: &Polygon<f64>) -> Vec<Point2D<f64>> {
+        polygon.vertices().collect()
+    }
+    
+    #[test_case(simplify_douglas_peucker, 2.0)]
+    #[test_case(simplify_visvalingam_whyatt, 2.0)]
+    fn test_simplification(simplify: Simplifier, tolerance: f64) {
+        let simplified = simplify(&[bumpy_square()], tolerance, false);
+        assert_eq!(vertices(&simplified[0]), [point2(0.0, 0.0), point2(10.0, 0.0), point2(10.0, 10.0), point2(0.0, 10.0)]);
+        
+        let not_simplified = simplify(&[bumpy_square()], 0.01, false);
+        assert_eq!(vertices(&not_simplified[0]), vertices(&bumpy_square()));
+    }
+    
+    #[test_case(simplify_douglas_peucker)]
+    #[test_case(simplify_visvalingam_whyatt)]
+    fn test_at_least_three_vertices_are_kept(simplify: Simplifier) {
+        let simplified = simplify(&[bumpy_square(), hole_in_bump()], 100.0, false);
+        assert_eq!(simplified[0].vertices().count(), 3);
+        assert_eq!(vertices(&simplified[1]), vertices(&hole_in_bump()));
+    }
+    
+    #[test_case(simplify_douglas_peucker, 2.0)]
+    #[test_case(simplify_visvalingam_whyatt, 2.0)]
+    fn test_topology_preservation(simplify: Simplifier, tolerance: f64) {
+        let polygons = [bumpy_square(), hole_in_bump()];
+        let hole_vertex = point2(5.0, 10.6);
+        
+        let simplified = simplify(&polygons, tolerance, false);
+        assert!(!simplified[0].contains(hole_vertex, FillRule::NonZero));
+        
+        let simplified = simplify(&polygons, tolerance, true);
+        assert!(simplified[0].contains(hole_vertex, FillRule::NonZero));
+        assert!(simplified[0].vertices().any(|vertex| vertex == point2(5.0, 11.0)));
+    }
+    
+    /// Pairs of indices of the polygons having common points,
+    /// including the ones of non-adjacent edges of the same polygon.
+    fn touching_pairs(polygons: &[Polygon<f64>]) -> HashSet<(usize, usize)> {
+        let edges: Vec<_> = polygons.iter().enumerate()
+            .flat_map(|(ring, polygon)| polygon.edges().enumerate().map(move |(index, edge)| (ring, index, edge)))
+            .collect();
+        let mut pairs = HashSet::new();
+        for (i, &(ring_a, index_a, (a0, a1))) in edges.iter().enumerate() {
+            let len = polygons[ring_a].vertices().count();
+            for &(ring_b, index_b, (b0, b1)) in &edges[i + 1..] {
+                let are_adjacent = ring_a == ring_b && (index_b == index_a + 1 || index_a == 0 && index_b == len - 1);
+                if !are_adjacent && segments_intersect(a0, a1, b0, b1) {
+                    pairs.insert((ring_a, ring_b));
+                }
+            }
+        }
+        pairs
+    }
+    
+    #[test_case("text_142x64_theos", simplify_douglas_peucker, 3.0)]
+    #[test_case("text_142x64_theos", simplify_visvalingam_whyatt, 10.0)]
+    #[test_case("pattern_164x164_ga", simplify_douglas_peucker, 3.0)]
+    #[test_case("pattern_164x164_ga", simplify_visvalingam_whyatt, 10.0)]
+    fn test_topology_preservation_in_collection(name: &str, simplify: Simplifier, tolerance: f64) {
+        let image = get_test_image(name).unwrap();
+        let contour_collection = ImageContourCollection::new(&image, false, Connectivity::Eight);
+        let polygons: Vec<_> = contour_collection.all_contours().map(|contour| to_accurate_polygon(&contour)).collect();
+        let simplified = simplify(&polygons, tolerance, true);
+        
+        let original_count: usize = polygons.iter().map(|polygon| polygon.vertices().count()).sum();
+        let simplified_count: usize = simplified.iter().map(|polygon| polygon.vertices().count()).sum();
+        assert!(simplified_count < original_count / 2);
+        
+        // Some contours touch each other at their corners
+        let touching = touching_pairs(&polygons);
+        for pair in touching_pairs(&simplified) {
+            assert!(touching.contains(&pair), "{name}: polygons {pair:?} intersect");
+        }
+        
+        let positions: HashMap<_, _> = contour_collection.all_contours().enumerate()
+            .map(|(position, contour)| (contour.id(), position))
+            .collect();
+        for (contour, polygon) in contour_collection.all_contours().zip(&simplified) {
+            let expected_orientation = if contour.is_outer() { Orientation::Clockwise } else { Orientation::Anticlockwise };
+            assert_eq!(polygon.orientation(), Some(expected_orientation));
+            if let Some(parent) = contour.parent() {
+                let parent_polygon = &simplified[positions[&parent.id()]];
+                let is_inside = |vertex| parent_polygon.contains(vertex, FillRule::NonZero)
+                    || parent_polygon.edges().any(|(p0, p1)| segment_distance(vertex, p0, p1) == 0.0);
+                assert!(polygon.vertices().all(is_inside), "{name}: contour {} is outside its parent", contour.id());
+            }
+        }
+    }
+}
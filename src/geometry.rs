@@ -1,13 +1,23 @@
 mod polygon;
 mod orthopolygon;
 mod rasterization;
+mod moments;
+mod chain_codes;
+mod boolean_operations;
+mod convex_hull;
+mod morphology;
 
 use std::fmt::Debug;
 use euclid::num::{Floor, Ceil, Round};
 use num_traits::{NumAssign, cast::NumCast};
-pub use polygon::{Polygon, Polygonlike};
+pub use polygon::{Polygon, Polygonlike, Orientation, FillRule};
 pub use orthopolygon::{Orthopolygon, Orthopolygonlike, PointPosition};
-pub use rasterization::draw_orthopolygons;
+pub use rasterization::{draw_orthopolygons, fill_orthopolygons, scan_orthopolygons};
+pub use moments::{Moments, CentralMoments};
+pub use chain_codes::{ChainCode, RdCodePoint, ChainCodeError};
+pub use boolean_operations::{BooleanOperation, Band, orthopolygon_bands, combine_bands};
+pub use morphology::{MorphologicalOperation, morph_bands};
+pub use convex_hull::{OrientedRect, orthopolygon_convex_hull};
 
 pub trait Number: Copy + PartialOrd + NumAssign + NumCast + Floor + Ceil + Round + Debug { }
 impl<T> Number for T where T: Copy + PartialOrd + NumAssign + NumCast + Floor + Ceil + Round + Debug { }
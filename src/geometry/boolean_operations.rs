@@ -0,0 +1,173 @@
+use super::{Orthopolygonlike, scan_orthopolygons};
+
+/// A boolean operation on two sets of pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOperation {
+    /// Pixels in either set.
+    Union,
+    /// Pixels in both sets.
+    Intersection,
+    /// Pixels in the first set but not in the second one.
+    Difference,
+    /// Pixels in exactly one of the sets.
+    SymmetricDifference,
+}
+
+impl BooleanOperation {
+    pub fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            Self::Union => a || b,
+            Self::Intersection => a && b,
+            Self::Difference => a && !b,
+            Self::SymmetricDifference => a != b,
+        }
+    }
+}
+
+/// A horizontal band `y0..y1` of a set of pixels
+/// with the same `runs` of pixels `x0..x1` in each row.
+/// The runs are sorted and do not touch each other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Band {
+    pub y0: i32,
+    pub y1: i32,
+    pub runs: Vec<(i32, i32)>,
+}
+
+/// Computes the bands of the pixels enclosed by the `orthopolygons`
+/// by the nonzero winding rule. So, overlapping orthopolygons are united,
+/// and anticlockwise holes inside clockwise outer contours are subtracted.
+/// 
+/// The bands are sorted from top to bottom, and there are no empty bands.
+pub fn orthopolygon_bands<'a, Ortho>(orthopolygons: impl Iterator<Item = &'a Ortho>) -> Vec<Band>
+where Ortho: Orthopolygonlike + 'a {
+    let mut bands = Vec::new();
+    scan_orthopolygons(orthopolygons, |y0, y1, crossings| {
+        let mut runs: Vec<(i32, i32)> = Vec::new();
+        let mut winding = 0;
+        let mut start = 0;
+        for &(x, direction) in crossings {
+            let was_inside = winding != 0;
+            winding += direction;
+            match (was_inside, winding != 0) {
+                (false, true) => start = x,
+                (true, false) => add_run(&mut runs, start, x),
+                _ => { },
+            }
+        }
+        add_band(&mut bands, Band { y0, y1, runs });
+    });
+    bands
+}
+
+/// Applies the boolean `operation` to two sets of pixels given by their bands.
+pub fn combine_bands(operation: BooleanOperation, a: &[Band], b: &[Band]) -> Vec<Band> {
+    let mut ys: Vec<_> = a.iter().chain(b).flat_map(|band| [band.y0, band.y1]).collect();
+    ys.sort_unstable();
+    ys.dedup();
+    
+    let mut bands = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (&y0, &y1) in ys.iter().zip(ys.iter().skip(1)) {
+        let runs = combine_runs(operation, runs_at(a, &mut i, y0), runs_at(b, &mut j, y0));
+        add_band(&mut bands, Band { y0, y1, runs });
+    }
+    bands
+}
+
+/// Gets the runs of the row `y` advancing the `index` of the current band.
+/// The rows must be requested from top to bottom.
+fn runs_at<'a>(bands: &'a [Band], index: &mut usize, y: i32) -> &'a [(i32, i32)] {
+    while *index < bands.len() && bands[*index].y1 <= y {
+        *index += 1;
+    }
+    match bands.get(*index) {
+        Some(band) if band.y0 <= y => &band.runs,
+        _ => &[],
+    }
+}
+
+/// Applies the boolean `operation` to two rows of runs.
+pub(super) fn combine_runs(operation: BooleanOperation, a: &[(i32, i32)], b: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    let mut xs: Vec<_> = a.iter().chain(b).flat_map(|&(x0, x1)| [x0, x1]).collect();
+    xs.sort_unstable();
+    xs.dedup();
+    
+    let mut runs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (&x0, &x1) in xs.iter().zip(xs.iter().skip(1)) {
+        while i < a.len() && a[i].1 <= x0 { i += 1 }
+        while j < b.len() && b[j].1 <= x0 { j += 1 }
+        let is_in_a = i < a.len() && a[i].0 <= x0;
+        let is_in_b = j < b.len() && b[j].0 <= x0;
+        if operation.apply(is_in_a, is_in_b) {
+            add_run(&mut runs, x0, x1);
+        }
+    }
+    runs
+}
+
+/// Adds a run merging it with the last one if they touch.
+fn add_run(runs: &mut Vec<(i32, i32)>, x0: i32, x1: i32) {
+    match runs.last_mut() {
+        Some(last) if last.1 == x0 => last.1 = x1,
+        _ if x0 < x1 => runs.push((x0, x1)),
+        _ => { },
+    }
+}
+
+/// Adds a band merging it with the last one if they touch and have the same runs.
+/// Empty bands are skipped.
+pub(super) fn add_band(bands: &mut Vec<Band>, band: Band) {
+    if band.runs.is_empty() {
+        return;
+    }
+    match bands.last_mut() {
+        Some(last) if last.y1 == band.y0 && last.runs == band.runs => last.y1 = band.y1,
+        _ => bands.push(band),
+    }
+}
+
+
+// ---------
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+    use euclid::point2;
+    use crate::geometry::Orthopolygon;
+    use super::*;
+    
+    fn rect(x0: i32, y0: i32, x1: i32, y1: i32) -> Orthopolygon {
+        Orthopolygon::from(vec![point2(x0, y0), point2(x1, y1)])
+    }
+    
+    #[test]
+    fn test_overlapping_and_touching_orthopolygons_are_united() {
+        let orthopolygons = [rect(0, 0, 4, 2), rect(2, 1, 6, 3), rect(6, 1, 8, 3)];
+        assert_eq!(orthopolygon_bands(orthopolygons.iter()), [
+            Band { y0: 0, y1: 1, runs: vec![(0, 4)] },
+            Band { y0: 1, y1: 2, runs: vec![(0, 8)] },
+            Band { y0: 2, y1: 3, runs: vec![(2, 8)] },
+        ]);
+    }
+    
+    #[test]
+    fn test_hole_is_subtracted() {
+        let hole = Orthopolygon::from(vec![point2(3, 1), point2(1, 2)]);
+        let orthopolygons = [rect(0, 0, 4, 3), hole];
+        assert_eq!(orthopolygon_bands(orthopolygons.iter()), [
+            Band { y0: 0, y1: 1, runs: vec![(0, 4)] },
+            Band { y0: 1, y1: 2, runs: vec![(0, 1), (3, 4)] },
+            Band { y0: 2, y1: 3, runs: vec![(0, 4)] },
+        ]);
+    }
+    
+    #[test_case(BooleanOperation::Union => vec![(0, 6), (7, 9)])]
+    #[test_case(BooleanOperation::Intersection => vec![(2, 3), (5, 6)])]
+    #[test_case(BooleanOperation::Difference => vec![(0, 2), (7, 9)])]
+    #[test_case(BooleanOperation::SymmetricDifference => vec![(0, 2), (3, 5), (7, 9)])]
+    fn test_combine_runs(operation: BooleanOperation) -> Vec<(i32, i32)> {
+        combine_runs(operation, &[(0, 3), (5, 6), (7, 9)], &[(2, 6)])
+    }
+}
@@ -0,0 +1,355 @@
+use std::fmt;
+use euclid::{default::{Point2D, Vector2D}, vec2};
+use crate::more_itertools::MoreIterTools;
+use super::{Orthopolygon, Orthopolygonlike};
+
+/// Freeman chain code: a start point and a sequence of directions,
+/// each meaning a unit step to a neighboring point.
+/// 
+/// 4-direction codes: 0 — right, 1 — up, 2 — left, 3 — down.
+/// 
+/// 8-direction codes: 0 — right, 1 — up-right, 2 — up, 3 — up-left,
+/// 4 — left, 5 — down-left, 6 — down, 7 — down-right.
+/// 
+/// “Up” means decreasing y, as the image rows go down.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainCode {
+    pub start: Point2D<i32>,
+    pub directions: Vec<u8>,
+}
+
+/// An even vertex of an orthopolygon with its Miyatake’s RD code (see `FeatureKind`):
+/// ```
+///  1: ●───┐   9: ┌───●   5: │   │  10: │   │
+///     │   │      │   │      └───●      ●───┘
+/// 
+///  3:     │   7: │       4: │       8:     │
+///     ●───┘      ●───┐      └───●      ┌───●
+///     │              │          │      │
+/// ```
+/// where the horizontal edge goes from the vertex (●) to the other end,
+/// and the area enclosed by the orthopolygon is on the right side of the edge.
+/// 
+/// Codes (2) and (6) denote vertical edges, they are not included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RdCodePoint {
+    pub point: Point2D<i32>,
+    pub code: u8,
+}
+
+/// Describes why an orthopolygon could not be reconstructed from a code.
+/// `index` is the index of the direction or the point in the code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainCodeError {
+    /// The direction is not a valid direction code.
+    InvalidDirection { index: usize },
+    /// The step goes back along the previous one.
+    Reversal { index: usize },
+    /// The steps do not lead back to the start point.
+    NotClosed,
+    /// The RD code of the point does not match its neighbors.
+    MismatchedRdCode { index: usize },
+}
+
+impl fmt::Display for ChainCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidDirection { index } => write!(f, "Direction {index} is invalid"),
+            Self::Reversal { index } => write!(f, "Step {index} goes back along the previous one"),
+            Self::NotClosed => write!(f, "Chain code is not closed"),
+            Self::MismatchedRdCode { index } => write!(f, "RD code of point {index} does not match the neighboring points"),
+        }
+    }
+}
+
+impl std::error::Error for ChainCodeError { }
+
+impl ChainCode {
+    /// Computes the 4-direction code of the orthopolygon’s boundary (the “crack code”)
+    /// given by its `even_vertices`, starting from the first one.
+    pub fn crack_code(even_vertices: impl Iterator<Item = Point2D<i32>>) -> Self {
+        let vertices: Vec<_> = even_vertices
+            .circular_pairs()
+            .flat_map(|(p0, p1)| [p0, Point2D::new(p1.x, p0.y)])
+            .collect();
+        let start = vertices.first().cloned().unwrap_or_default();
+        let directions = vertices.into_iter()
+            .circular_pairs()
+            .filter(|(p0, p1)| p0 != p1)
+            .flat_map(|(p0, p1)| {
+                let length = (p1.x - p0.x).abs() + (p1.y - p0.y).abs();
+                let direction = direction_4((p1 - p0).map(i32::signum));
+                (0..length).map(move |_| direction)
+            })
+            .collect();
+        Self { start, directions }
+    }
+    
+    /// Computes the 8-direction code of the chain of pixels adjacent to the boundary
+    /// of the orthopolygon given by its `even_vertices`.
+    /// 
+    /// Each unit step along the boundary is assigned the pixel on its right side,
+    /// so the chain goes around the pixels the same way as the orthopolygon does.
+    /// For a clockwise orthopolygon (an outer contour), these are its boundary pixels.
+    /// For an anticlockwise one (a hole), these are the foreground pixels surrounding it.
+    /// A single pixel has no directions.
+    /// The orthopolygon should not be empty.
+    pub fn pixel_code(even_vertices: impl Iterator<Item = Point2D<i32>>) -> Self {
+        let crack_code = Self::crack_code(even_vertices);
+        let mut pixels: Vec<Point2D<i32>> = Vec::new();
+        let mut point = crack_code.start;
+        for &direction in &crack_code.directions {
+            let pixel = point + PIXEL_ON_RIGHT[direction as usize];
+            if pixels.last() != Some(&pixel) {
+                pixels.push(pixel);
+            }
+            point += STEPS_4[direction as usize];
+        }
+        while pixels.len() > 1 && pixels.first() == pixels.last() {
+            pixels.pop();
+        }
+        
+        let start = pixels.first().cloned().unwrap_or(crack_code.start);
+        let directions = if pixels.len() > 1 {
+            pixels.into_iter().circular_pairs().map(|(p0, p1)| direction_8(p1 - p0)).collect()
+        } else {
+            Vec::new()
+        };
+        Self { start, directions }
+    }
+}
+
+impl RdCodePoint {
+    /// Computes the RD codes of the orthopolygon’s `even_vertices`.
+    pub fn of_orthopolygon(even_vertices: impl Iterator<Item = Point2D<i32>>) -> Vec<Self> {
+        let vertices: Vec<_> = even_vertices.collect();
+        let count = vertices.len();
+        (0..count).map(|index| {
+            let previous = vertices[(index + count - 1) % count];
+            let point = vertices[index];
+            let next = vertices[(index + 1) % count];
+            
+            let goes_right = next.x > point.x;
+            let (start_goes_down, end_goes_down) = (previous.y > point.y, next.y > point.y);
+            let (left_goes_down, right_goes_down) =
+                if goes_right { (start_goes_down, end_goes_down) } else { (end_goes_down, start_goes_down) };
+            let code = match (left_goes_down, right_goes_down, goes_right) {
+                (true, true, true) => 1,
+                (true, false, true) => 3,
+                (false, false, false) => 5,
+                (false, true, true) => 7,
+                (true, true, false) => 9,
+                (false, true, false) => 4,
+                (true, false, false) => 8,
+                (false, false, true) => 10,
+            };
+            Self { point, code }
+        }).collect()
+    }
+}
+
+impl Orthopolygon {
+    /// Reconstructs an orthopolygon from its 4-direction boundary code,
+    /// see `Orthopolygonlike::freeman_code_4`.
+    /// 
+    /// The first even vertex is the start point if the code starts with a horizontal step
+    /// after a vertical one. Otherwise, the first even vertex is the next one.
+    pub fn from_freeman_code_4(chain_code: &ChainCode) -> Result<Self, ChainCodeError> {
+        // Start points and directions of the straight segments
+        let mut segments: Vec<(Point2D<i32>, u8)> = Vec::new();
+        let mut point = chain_code.start;
+        for (index, &direction) in chain_code.directions.iter().enumerate() {
+            let step = *STEPS_4.get(direction as usize).ok_or(ChainCodeError::InvalidDirection { index })?;
+            match segments.last() {
+                Some(&(_, last)) if last == direction => { },
+                Some(&(_, last)) if is_reversal(last, direction) => return Err(ChainCodeError::Reversal { index }),
+                _ => segments.push((point, direction)),
+            }
+            point += step;
+        }
+        if point != chain_code.start {
+            return Err(ChainCodeError::NotClosed);
+        }
+        
+        // The first and the last segments can be parts of the same edge
+        if let (Some(&(_, first)), Some(&(last_start, last))) = (segments.first(), segments.last()) {
+            if segments.len() > 1 && first == last {
+                segments.pop();
+                segments[0].0 = last_start;
+            } else if is_reversal(last, first) {
+                return Err(ChainCodeError::Reversal { index: 0 });
+            }
+        }
+        
+        let even_vertices = segments.into_iter()
+            .filter(|&(_, direction)| direction % 2 == 0)
+            .map(|(start, _)| start);
+        Ok(Self::new(even_vertices))
+    }
+    
+    /// Reconstructs an orthopolygon from the 8-direction code of its boundary pixels,
+    /// see `Orthopolygonlike::freeman_code_8`.
+    pub fn from_freeman_code_8(chain_code: &ChainCode) -> Result<Self, ChainCodeError> {
+        if let Some(index) = chain_code.directions.iter().position(|&direction| direction >= 8) {
+            return Err(ChainCodeError::InvalidDirection { index });
+        }
+        // A pixel side is denoted by the 4-direction code of the step along it.
+        // Moving straight keeps the side, moving diagonally turns it to the left.
+        let departure_side = |direction: u8| direction / 2;
+        let arrival_side = |direction: u8| (direction / 2 + direction % 2) % 4;
+        
+        // The boundary goes around each pixel clockwise from its arrival side to its departure side
+        let mut side = chain_code.directions.last().map_or(0, |&direction| arrival_side(direction));
+        let start = chain_code.start + SIDE_STARTS[side as usize];
+        let mut pixel = chain_code.start;
+        let mut sides = Vec::new();
+        for &direction in &chain_code.directions {
+            while side != departure_side(direction) {
+                sides.push(side);
+                side = (side + 3) % 4;
+            }
+            sides.push(side);
+            side = arrival_side(direction);
+            pixel += STEPS_8[direction as usize];
+        }
+        if pixel != chain_code.start {
+            return Err(ChainCodeError::NotClosed);
+        }
+        if chain_code.directions.is_empty() {
+            sides.extend([0, 3, 2, 1]);
+        }
+        Self::from_freeman_code_4(&ChainCode { start, directions: sides })
+    }
+    
+    /// Reconstructs an orthopolygon from the RD codes of its even vertices,
+    /// see `Orthopolygonlike::rd_code`.
+    /// The codes are checked to match the vertices.
+    pub fn from_rd_code(rd_code: &[RdCodePoint]) -> Result<Self, ChainCodeError> {
+        let orthopolygon = Self::new(rd_code.iter().map(|rd_point| rd_point.point));
+        let expected = RdCodePoint::of_orthopolygon(orthopolygon.even_vertices());
+        match rd_code.iter().zip(expected).position(|(actual, expected)| actual.code != expected.code) {
+            Some(index) => Err(ChainCodeError::MismatchedRdCode { index }),
+            None => Ok(orthopolygon),
+        }
+    }
+}
+
+fn is_reversal(direction: u8, next_direction: u8) -> bool {
+    (direction + 2) % 4 == next_direction
+}
+
+fn direction_4(step: Vector2D<i32>) -> u8 {
+    STEPS_4.iter().position(|&s| s == step).unwrap() as u8
+}
+
+fn direction_8(step: Vector2D<i32>) -> u8 {
+    STEPS_8.iter().position(|&s| s == step).unwrap() as u8
+}
+
+const STEPS_4: [Vector2D<i32>; 4] = [vec2(1, 0), vec2(0, -1), vec2(-1, 0), vec2(0, 1)];
+
+const STEPS_8: [Vector2D<i32>; 8] = [vec2(1, 0), vec2(1, -1), vec2(0, -1), vec2(-1, -1), vec2(-1, 0), vec2(-1, 1), vec2(0, 1), vec2(1, 1)];
+
+/// Offset of the pixel on the right side of a unit step from a point in each of the 4 directions.
+const PIXEL_ON_RIGHT: [Vector2D<i32>; 4] = [vec2(0, 0), vec2(0, -1), vec2(-1, -1), vec2(-1, 0)];
+
+/// Offset of the start of each pixel side from the pixel,
+/// the sides being traversed clockwise.
+const SIDE_STARTS: [Vector2D<i32>; 4] = [vec2(0, 0), vec2(0, 1), vec2(1, 1), vec2(1, 0)];
+
+
+// ---------
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+    use euclid::point2;
+    use crate::geometry::Orientation;
+    use super::*;
+    
+    /// ```
+    /// ┌───────┐
+    /// │       │
+    /// │   ┌───┘
+    /// │   │
+    /// └───┘
+    /// ```
+    fn l_shape() -> Orthopolygon {
+        Orthopolygon::from(vec![point2(0, 0), point2(4, 2), point2(2, 4)])
+    }
+    
+    #[test]
+    fn test_freeman_code_4() {
+        let chain_code = l_shape().freeman_code_4();
+        assert_eq!(chain_code.start, point2(0, 0));
+        assert_eq!(chain_code.directions, [0, 0, 0, 0, 3, 3, 2, 2, 3, 3, 2, 2, 1, 1, 1, 1]);
+        assert_eq!(Orthopolygon::from_freeman_code_4(&chain_code), Ok(l_shape()));
+    }
+    
+    #[test]
+    fn test_freeman_code_8() {
+        let chain_code = l_shape().freeman_code_8();
+        assert_eq!(chain_code.start, point2(0, 0));
+        assert_eq!(chain_code.directions, [0, 0, 0, 6, 4, 5, 6, 4, 2, 2, 2]);
+        assert_eq!(Orthopolygon::from_freeman_code_8(&chain_code), Ok(l_shape()));
+    }
+    
+    #[test]
+    fn test_freeman_code_8_of_single_pixel() {
+        let pixel = Orthopolygon::from(vec![point2(3, 5), point2(4, 6)]);
+        let chain_code = pixel.freeman_code_8();
+        assert_eq!(chain_code, ChainCode { start: point2(3, 5), directions: vec![] });
+        assert_eq!(Orthopolygon::from_freeman_code_8(&chain_code), Ok(pixel));
+    }
+    
+    #[test]
+    fn test_freeman_code_8_of_single_pixel_hole() {
+        // Anticlockwise, so the chain goes through the 4-neighbors of the hole
+        let hole = Orthopolygon::from(vec![point2(4, 5), point2(3, 6)]);
+        let chain_code = hole.freeman_code_8();
+        assert_eq!(chain_code, ChainCode { start: point2(3, 4), directions: vec![5, 7, 1, 3] });
+    }
+    
+    #[test]
+    fn test_chain_codes_with_zero_length_edges() {
+        let orthopolygon = Orthopolygon::from(vec![point2(2, 2), point2(2, 4), point2(0, 4), point2(0, 0), point2(4, 2)]);
+        let chain_code = orthopolygon.freeman_code_4();
+        assert_eq!(chain_code.start, point2(2, 2));
+        assert_eq!(chain_code.directions, [3, 3, 2, 2, 1, 1, 1, 1, 0, 0, 0, 0, 3, 3, 2, 2]);
+        let restored = Orthopolygon::from_freeman_code_4(&chain_code).unwrap();
+        assert_eq!(restored.to_canonical(Orientation::Clockwise), l_shape());
+        
+        let chain_code = orthopolygon.freeman_code_8();
+        let restored = Orthopolygon::from_freeman_code_8(&chain_code).unwrap();
+        assert_eq!(restored.to_canonical(Orientation::Clockwise), l_shape());
+    }
+    
+    #[test]
+    fn test_rd_code() {
+        let codes: Vec<_> = l_shape().rd_code().iter().map(|rd_point| rd_point.code).collect();
+        assert_eq!(codes, [1, 8, 5]);
+        assert_eq!(Orthopolygon::from_rd_code(&l_shape().rd_code()), Ok(l_shape()));
+    }
+    
+    #[test]
+    fn test_start_in_the_middle_of_an_edge() {
+        let chain_code = ChainCode { start: point2(1, 0), directions: vec![0, 3, 2, 2, 1, 0] };
+        let expected = Orthopolygon::from(vec![point2(0, 0), point2(2, 1)]);
+        assert_eq!(Orthopolygon::from_freeman_code_4(&chain_code), Ok(expected));
+    }
+    
+    #[test_case(vec![0, 3, 2, 4] => Err(ChainCodeError::InvalidDirection { index: 3 }))]
+    #[test_case(vec![0, 3, 1, 2] => Err(ChainCodeError::Reversal { index: 2 }))]
+    #[test_case(vec![0, 0, 3, 2, 1, 2] => Err(ChainCodeError::Reversal { index: 0 }); "reversal at the start")]
+    #[test_case(vec![0, 3, 2] => Err(ChainCodeError::NotClosed); "open")]
+    fn test_invalid_freeman_code_4(directions: Vec<u8>) -> Result<Orthopolygon, ChainCodeError> {
+        Orthopolygon::from_freeman_code_4(&ChainCode { start: point2(0, 0), directions })
+    }
+    
+    #[test]
+    fn test_mismatched_rd_code() {
+        let mut rd_code = l_shape().rd_code();
+        rd_code[1].code = 3;
+        assert_eq!(Orthopolygon::from_rd_code(&rd_code), Err(ChainCodeError::MismatchedRdCode { index: 1 }));
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+use euclid::Angle;
+use euclid::default::{Point2D, Size2D, Vector2D};
+use crate::more_itertools::MoreIterTools;
+use super::{Number, Polygon};
+
+/// A rectangle rotated by `angle` about its `center`.
+/// 
+/// `size.width` is measured along the direction of the `angle`,
+/// and `size.height` across it. The angle is in the range (−45°, 45°],
+/// so that the sides closer to horizontal are the width.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrientedRect {
+    pub center: Point2D<f64>,
+    pub size: Size2D<f64>,
+    pub angle: Angle<f64>,
+}
+
+impl OrientedRect {
+    /// Finds the minimum-area rectangle enclosing a convex polygon
+    /// given by its `hull` vertices, or `None` if its area is zero.
+    /// 
+    /// One of the sides of the minimum-area rectangle
+    /// always lies on an edge of the hull, so all the edges are tried.
+    pub(super) fn enclosing(hull: &[Point2D<f64>]) -> Option<Self> {
+        let mut best: Option<(f64, Self)> = None;
+        for (p0, p1) in hull.iter().copied().circular_pairs() {
+            let Some(along) = (p1 - p0).try_normalize() else { continue };
+            let across = Vector2D::new(-along.y, along.x);
+            let (min_along, max_along) = min_max(hull.iter().map(|p| p.to_vector().dot(along)));
+            let (min_across, max_across) = min_max(hull.iter().map(|p| p.to_vector().dot(across)));
+            
+            let area = (max_along - min_along) * (max_across - min_across);
+            if area > 0.0 && best.is_none_or(|(best_area, _)| area < best_area) {
+                let center = (along * (min_along + max_along) / 2.0 + across * (min_across + max_across) / 2.0).to_point();
+                let size = Size2D::new(max_along - min_along, max_across - min_across);
+                best = Some((area, Self::normalized(center, size, along.y.atan2(along.x))));
+            }
+        }
+        best.map(|(_, rect)| rect)
+    }
+    
+    /// Turns the rect by a multiple of 90°, so that the angle is in the range (−45°, 45°].
+    fn normalized(center: Point2D<f64>, size: Size2D<f64>, angle: f64) -> Self {
+        let mut quarter_turns = (angle / FRAC_PI_2).round();
+        let mut angle = angle - quarter_turns * FRAC_PI_2;
+        if angle <= -FRAC_PI_4 {
+            angle += FRAC_PI_2;
+            quarter_turns -= 1.0;
+        }
+        // After an odd number of quarter turns, the width is measured across the new angle
+        let size = if quarter_turns.rem_euclid(2.0) == 1.0 { Size2D::new(size.height, size.width) } else { size };
+        Self { center, size, angle: Angle::radians(angle) }
+    }
+    
+    /// Area of the rect.
+    pub fn area(&self) -> f64 {
+        self.size.area()
+    }
+    
+    /// The corners of the rect, clockwise (with the y axis pointing down).
+    pub fn corners(&self) -> [Point2D<f64>; 4] {
+        let (sin, cos) = self.angle.sin_cos();
+        let along = Vector2D::new(cos, sin) * self.size.width / 2.0;
+        let across = Vector2D::new(-sin, cos) * self.size.height / 2.0;
+        [
+            self.center - along - across,
+            self.center + along - across,
+            self.center + along + across,
+            self.center - along + across,
+        ]
+    }
+}
+
+/// Convex hull of an orthopolygon given by its `even_vertices`,
+/// clockwise, starting from the leftmost of the topmost vertices.
+/// 
+/// Since all the vertices lie on the horizontal edges,
+/// only the ends of the leftmost and rightmost edge in each row
+/// are candidates for the hull, and they are naturally sorted by rows.
+pub fn orthopolygon_convex_hull(even_vertices: impl Iterator<Item = Point2D<i32>>) -> Polygon<i32> {
+    let mut rows = BTreeMap::new();
+    for (p0, p1) in even_vertices.circular_pairs() {
+        let (x0, x1) = (p0.x.min(p1.x), p0.x.max(p1.x));
+        let (min_x, max_x) = rows.entry(p0.y).or_insert((x0, x1));
+        *min_x = x0.min(*min_x);
+        *max_x = x1.max(*max_x);
+    }
+    let mut points: Vec<_> = rows.into_iter()
+        .flat_map(|(y, (min_x, max_x))| [Point2D::new(min_x, y), Point2D::new(max_x, y)])
+        .collect();
+    points.dedup();
+    Polygon::from(hull_of_sorted(&points))
+}
+
+/// Convex hull of `points` in any order,
+/// clockwise, starting from the leftmost of the topmost points.
+/// The points with infinite or NaN coordinates are ignored.
+pub(super) fn convex_hull<N: Number>(points: impl Iterator<Item = Point2D<N>>) -> Vec<Point2D<N>> {
+    let mut points: Vec<_> = points
+        .filter(|point| point.to_f64().to_array().iter().all(|coordinate| coordinate.is_finite()))
+        .collect();
+    // Finite coordinates are totally ordered
+    points.sort_by(|a, b| (a.y, a.x).partial_cmp(&(b.y, b.x)).unwrap());
+    points.dedup();
+    hull_of_sorted(&points)
+}
+
+/// Andrew’s monotone chain algorithm for `points` sorted by y, then by x.
+/// The right chain goes down, and the left one goes up, both turning clockwise.
+fn hull_of_sorted<N: Number>(points: &[Point2D<N>]) -> Vec<Point2D<N>> {
+    if points.len() < 3 {
+        let mut hull = points.to_vec();
+        hull.dedup();
+        return hull;
+    }
+    let turns_clockwise = |p0: Point2D<N>, p1: Point2D<N>, p2: Point2D<N>| {
+        let (p0, p1, p2) = (p0.to_f64(), p1.to_f64(), p2.to_f64());
+        (p1 - p0).cross(p2 - p0) > 0.0
+    };
+    
+    let mut hull: Vec<Point2D<N>> = Vec::with_capacity(points.len() + 1);
+    let push = |hull: &mut Vec<Point2D<N>>, chain_start: usize, point: Point2D<N>| {
+        while hull.len() >= chain_start + 2
+            && !turns_clockwise(hull[hull.len() - 2], hull[hull.len() - 1], point) {
+            hull.pop();
+        }
+        hull.push(point);
+    };
+    for &point in points {
+        push(&mut hull, 0, point);
+    }
+    let chain_start = hull.len() - 1;
+    for &point in points.iter().rev().skip(1) {
+        push(&mut hull, chain_start, point);
+    }
+    // The last point is the first one
+    hull.pop();
+    hull
+}
+
+fn min_max(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| (min.min(value), max.max(value)))
+}
+
+
+// ---------
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+    use euclid::{point2, size2};
+    use crate::geometry::{Orthopolygon, Orthopolygonlike, Polygonlike};
+    use super::*;
+    
+    /// ```
+    /// ┌───────┐
+    /// │       │
+    /// │   ┌───┘
+    /// │   │
+    /// └───┘
+    /// ```
+    fn l_shape() -> Orthopolygon {
+        Orthopolygon::from(vec![point2(0, 0), point2(4, 2), point2(2, 4)])
+    }
+    
+    fn rotated_rect(angle: f64) -> Polygon<f64> {
+        let rect = OrientedRect { center: point2(10.0, 5.0), size: size2(4.0, 2.0), angle: Angle::radians(angle.to_radians()) };
+        Polygon::from(rect.corners().to_vec())
+    }
+    
+    #[test]
+    fn test_convex_hull() {
+        let expected = [point2(0, 0), point2(4, 0), point2(4, 2), point2(2, 4), point2(0, 4)];
+        assert_eq!(l_shape().convex_hull().vertices().collect::<Vec<_>>(), expected);
+        assert_eq!(l_shape().to_polygon::<i32>().convex_hull().vertices().collect::<Vec<_>>(), expected);
+        assert_eq!(l_shape().rotated_180().convex_hull().signed_area(), 14.0);
+    }
+    
+    #[test]
+    fn test_convex_hull_of_degenerate_polygon() {
+        let segment = Polygon::from(vec![point2(0, 0), point2(1, 1), point2(2, 2), point2(1, 1)]);
+        assert_eq!(segment.convex_hull().vertices().collect::<Vec<_>>(), [point2(0, 0), point2(2, 2)]);
+        assert_eq!(segment.min_area_rect(), None);
+        assert_eq!(segment.solidity(), None);
+    }
+    
+    #[test]
+    fn test_convex_hull_ignores_non_finite_points() {
+        let polygon = Polygon::from(vec![
+            point2(0.0, 0.0), point2(2.0, 0.0), point2(f64::NAN, 1.0), point2(2.0, 2.0),
+            point2(1.0, f64::INFINITY), point2(0.0, 2.0),
+        ]);
+        let expected = [point2(0.0, 0.0), point2(2.0, 0.0), point2(2.0, 2.0), point2(0.0, 2.0)];
+        assert_eq!(polygon.convex_hull().vertices().collect::<Vec<_>>(), expected);
+    }
+    
+    #[test]
+    fn test_measurements() {
+        let shape = l_shape();
+        let rect = shape.min_area_rect().unwrap();
+        assert_eq!((rect.center, rect.size, rect.angle.radians), (point2(2.0, 2.0), size2(4.0, 4.0), 0.0));
+        assert_eq!(shape.solidity(), Some(12.0 / 14.0));
+        assert_eq!(shape.rectangularity(), Some(0.75));
+    }
+    
+    #[test_case(0.0 => (4.0, 2.0, 0.0))]
+    #[test_case(30.0 => (4.0, 2.0, 30.0))]
+    #[test_case(45.0 => (4.0, 2.0, 45.0))]
+    #[test_case(60.0 => (2.0, 4.0, -30.0))]
+    #[test_case(-50.0 => (2.0, 4.0, 40.0))]
+    #[test_case(170.0 => (4.0, 2.0, -10.0))]
+    fn test_min_area_rect(angle: f64) -> (f64, f64, f64) {
+        let polygon = rotated_rect(angle);
+        let rect = polygon.min_area_rect().unwrap();
+        assert!((rect.center - point2(10.0, 5.0)).length() < 1e-9);
+        assert!((polygon.rectangularity().unwrap() - 1.0).abs() < 1e-9);
+        let round = |value: f64| (value * 1e6).round() / 1e6;
+        (round(rect.size.width), round(rect.size.height), round(rect.angle.radians.to_degrees()))
+    }
+}
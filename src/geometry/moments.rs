@@ -0,0 +1,227 @@
+use std::ops::{Add, Sub};
+use euclid::default::{Point2D, Vector2D};
+use crate::more_itertools::MoreIterTools;
+
+/// Raw moments `m_pq = Σ x^p·y^q` of order up to 3 of a set of pixels,
+/// where (x, y) are the pixel coordinates relative to some origin.
+/// 
+/// The pixel (x, y) is the one whose upper-left corner is (x, y).
+/// The moments are exact integers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Moments {
+    pub m00: i128,
+    pub m10: i128,
+    pub m01: i128,
+    pub m20: i128,
+    pub m11: i128,
+    pub m02: i128,
+    pub m30: i128,
+    pub m21: i128,
+    pub m12: i128,
+    pub m03: i128,
+}
+
+/// Central moments `μ_pq = Σ (x − x̄)^p·(y − ȳ)^q` of order up to 3 of a set of pixels.
+/// `mu00` is the pixel count, the first order moments are always zero.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CentralMoments {
+    pub mu00: f64,
+    pub mu20: f64,
+    pub mu11: f64,
+    pub mu02: f64,
+    pub mu30: f64,
+    pub mu21: f64,
+    pub mu12: f64,
+    pub mu03: f64,
+}
+
+impl Moments {
+    /// Computes the moments of the pixels enclosed by an orthopolygon
+    /// given by its `even_vertices`, relative to the `origin`.
+    /// 
+    /// Each horizontal edge adds or subtracts the pixels of the columns it spans
+    /// from the origin row down to the edge. So, the moments are positive
+    /// for clockwise orthopolygons and negative for anticlockwise ones.
+    pub fn of_orthopolygon(even_vertices: impl Iterator<Item = Point2D<i32>>, origin: Point2D<i32>) -> Self {
+        let mut moments = Self::default();
+        for (start, end) in even_vertices.circular_pairs() {
+            let (x0, x1) = ((start.x - origin.x) as i128, (end.x - origin.x) as i128);
+            let y = (start.y - origin.y) as i128;
+            if x0 == x1 {
+                continue;
+            }
+            let x = [0, 1, 2, 3].map(|p| power_sum(p, x0) - power_sum(p, x1));
+            let y = [0, 1, 2, 3].map(|q| power_sum(q, y));
+            
+            moments.m00 += x[0] * y[0];
+            moments.m10 += x[1] * y[0];
+            moments.m01 += x[0] * y[1];
+            moments.m20 += x[2] * y[0];
+            moments.m11 += x[1] * y[1];
+            moments.m02 += x[0] * y[2];
+            moments.m30 += x[3] * y[0];
+            moments.m21 += x[2] * y[1];
+            moments.m12 += x[1] * y[2];
+            moments.m03 += x[0] * y[3];
+        }
+        moments
+    }
+    
+    /// Mean pixel position relative to the origin of the moments,
+    /// or `None` if there are no pixels.
+    /// 
+    /// Since pixel (x, y) is the one whose upper-left corner is (x, y),
+    /// half a pixel should be added to get the centroid of the area covered by the pixels.
+    pub fn mean(&self) -> Option<Vector2D<f64>> {
+        if self.m00 == 0 {
+            return None;
+        }
+        let m00 = self.m00 as f64;
+        Some(Vector2D::new(self.m10 as f64 / m00, self.m01 as f64 / m00))
+    }
+    
+    /// Computes central moments.
+    /// They do not depend on the origin of the raw moments,
+    /// but an origin close to the pixels gives more precise results.
+    pub fn central(&self) -> CentralMoments {
+        let Some(Vector2D { x, y, .. }) = self.mean() else { return CentralMoments::default() };
+        let [m00, m10, m01, m20, m11, m02, m30, m21, m12, m03] = [
+            self.m00, self.m10, self.m01, self.m20, self.m11,
+            self.m02, self.m30, self.m21, self.m12, self.m03,
+        ].map(|m| m as f64);
+        
+        CentralMoments {
+            mu00: m00,
+            mu20: m20 - x * m10,
+            mu11: m11 - x * m01,
+            mu02: m02 - y * m01,
+            mu30: m30 - 3.0 * x * m20 + 2.0 * x * x * m10,
+            mu21: m21 - 2.0 * x * m11 - y * m20 + 2.0 * x * x * m01,
+            mu12: m12 - 2.0 * y * m11 - x * m02 + 2.0 * y * y * m10,
+            mu03: m03 - 3.0 * y * m02 + 2.0 * y * y * m01,
+        }
+    }
+}
+
+impl Add for Moments {
+    type Output = Self;
+    
+    fn add(self, other: Self) -> Self {
+        Self {
+            m00: self.m00 + other.m00,
+            m10: self.m10 + other.m10,
+            m01: self.m01 + other.m01,
+            m20: self.m20 + other.m20,
+            m11: self.m11 + other.m11,
+            m02: self.m02 + other.m02,
+            m30: self.m30 + other.m30,
+            m21: self.m21 + other.m21,
+            m12: self.m12 + other.m12,
+            m03: self.m03 + other.m03,
+        }
+    }
+}
+
+impl Sub for Moments {
+    type Output = Self;
+    
+    fn sub(self, other: Self) -> Self {
+        Self {
+            m00: self.m00 - other.m00,
+            m10: self.m10 - other.m10,
+            m01: self.m01 - other.m01,
+            m20: self.m20 - other.m20,
+            m11: self.m11 - other.m11,
+            m02: self.m02 - other.m02,
+            m30: self.m30 - other.m30,
+            m21: self.m21 - other.m21,
+            m12: self.m12 - other.m12,
+            m03: self.m03 - other.m03,
+        }
+    }
+}
+
+impl CentralMoments {
+    /// Computes the seven Hu’s moment invariants[^1].
+    /// They do not change when the pixels are translated, scaled or rotated.
+    /// The 7th one changes its sign under reflection.
+    /// 
+    /// Returns zeros if there are no pixels.
+    /// 
+    /// [^1]: Ming-Kuei Hu, 1962:
+    /// _Visual pattern recognition by moment invariants_.
+    pub fn hu_invariants(&self) -> [f64; 7] {
+        if self.mu00 == 0.0 {
+            return [0.0; 7];
+        }
+        // Normalized central moments
+        let eta = |mu: f64, order: i32| mu / self.mu00.powf(1.0 + order as f64 / 2.0);
+        let (n20, n11, n02) = (eta(self.mu20, 2), eta(self.mu11, 2), eta(self.mu02, 2));
+        let (n30, n21, n12, n03) = (eta(self.mu30, 3), eta(self.mu21, 3), eta(self.mu12, 3), eta(self.mu03, 3));
+        
+        let (a, b) = (n30 + n12, n21 + n03);
+        let (c, d) = (n30 - 3.0 * n12, 3.0 * n21 - n03);
+        [
+            n20 + n02,
+            (n20 - n02).powi(2) + 4.0 * n11 * n11,
+            c * c + d * d,
+            a * a + b * b,
+            c * a * (a * a - 3.0 * b * b) + d * b * (3.0 * a * a - b * b),
+            (n20 - n02) * (a * a - b * b) + 4.0 * n11 * a * b,
+            d * a * (a * a - 3.0 * b * b) - c * b * (3.0 * a * a - b * b),
+        ]
+    }
+}
+
+/// Sum of `t^p` for `t` in `0..n`, extended to negative `n` by the same polynomial,
+/// so that `power_sum(p, b) − power_sum(p, a)` is the sum for `t` in `a..b`.
+fn power_sum(p: usize, n: i128) -> i128 {
+    match p {
+        0 => n,
+        1 => n * (n - 1) / 2,
+        2 => (n - 1) * n * (2 * n - 1) / 6,
+        3 => (n * (n - 1) / 2).pow(2),
+        _ => unreachable!(),
+    }
+}
+
+
+// ---------
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+    use super::*;
+    
+    #[test_case(0, -3, 4)]
+    #[test_case(1, -5, 7)]
+    #[test_case(2, -4, 3)]
+    #[test_case(3, -6, 2)]
+    #[test_case(3, 2, 9)]
+    fn test_power_sum(p: u32, a: i128, b: i128) {
+        let expected: i128 = (a..b).map(|t| t.pow(p)).sum();
+        assert_eq!(power_sum(p as usize, b) - power_sum(p as usize, a), expected);
+    }
+    
+    #[test]
+    fn test_hu_invariants_of_rotated_shape() {
+        // An L-shaped set of pixels and the same set rotated by 90°
+        let pixels = [(0, 0), (0, 1), (0, 2), (1, 2)];
+        let rotated = pixels.map(|(x, y)| (-y, x));
+        let moments = |pixels: [(i128, i128); 4]| {
+            let mut m = Moments::default();
+            for (x, y) in pixels {
+                m.m00 += 1;
+                m.m10 += x; m.m01 += y;
+                m.m20 += x * x; m.m11 += x * y; m.m02 += y * y;
+                m.m30 += x * x * x; m.m21 += x * x * y; m.m12 += x * y * y; m.m03 += y * y * y;
+            }
+            m
+        };
+        let expected = moments(pixels).central().hu_invariants();
+        let actual = moments(rotated).central().hu_invariants();
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-12, "{actual:?} != {expected:?}");
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use super::{Band, BooleanOperation};
+use super::boolean_operations::{combine_runs, add_band};
+
+/// A morphological operation on a set of pixels
+/// with a square structuring element of (2·radius + 1)×(2·radius + 1) pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MorphologicalOperation {
+    /// Pixels within the radius of a pixel of the set (in the maximum norm).
+    Dilation,
+    /// Pixels whose whole neighborhood within the radius is in the set.
+    Erosion,
+    /// Erosion followed by dilation, removing details smaller than the structuring element.
+    Opening,
+    /// Dilation followed by erosion, filling gaps smaller than the structuring element.
+    Closing,
+}
+
+/// Applies the morphological `operation` with the given `radius`
+/// to a set of pixels given by its bands, see `orthopolygon_bands`.
+/// The pixels outside the bands are background.
+/// 
+/// The work depends on the number of bands and runs, not on their height or width.
+/// Returns `None` if the dilated set does not fit in `i32` coordinates.
+pub fn morph_bands(operation: MorphologicalOperation, bands: &[Band], radius: u32) -> Option<Vec<Band>> {
+    match operation {
+        MorphologicalOperation::Dilation => dilate(bands, radius),
+        MorphologicalOperation::Erosion => Some(erode(bands, radius)),
+        MorphologicalOperation::Opening => dilate(&erode(bands, radius), radius),
+        MorphologicalOperation::Closing => dilate(bands, radius).map(|dilated| erode(&dilated, radius)),
+    }
+}
+
+/// Dilation is separable: the runs are widened first,
+/// and then each row is united with its neighbors.
+fn dilate(bands: &[Band], radius: u32) -> Option<Vec<Band>> {
+    let radius = radius as i64;
+    let mut widened = Vec::with_capacity(bands.len());
+    for band in bands {
+        let mut runs: Vec<(i32, i32)> = Vec::with_capacity(band.runs.len());
+        for &(x0, x1) in &band.runs {
+            let x0 = i32::try_from(x0 as i64 - radius).ok()?;
+            let x1 = i32::try_from(x1 as i64 + radius).ok()?;
+            match runs.last_mut() {
+                Some(last) if x0 <= last.1 => last.1 = x1,
+                _ => runs.push((x0, x1)),
+            }
+        }
+        widened.push(Band { runs, ..*band });
+    }
+    combine_windows(&widened, radius, |window, _| window.iter()
+        .fold(Vec::new(), |runs, band| combine_runs(BooleanOperation::Union, &runs, &band.runs)))
+}
+
+/// Erosion is separable: the runs are narrowed first,
+/// and then each row is intersected with its neighbors.
+fn erode(bands: &[Band], radius: u32) -> Vec<Band> {
+    let radius = radius as i64;
+    let narrowed: Vec<_> = bands.iter()
+        .map(|band| {
+            // Fully eroded bands are kept, so that they still cover their rows
+            let runs = band.runs.iter()
+                .filter(|&&(x0, x1)| x1 as i64 - x0 as i64 > 2 * radius)
+                .map(|&(x0, x1)| ((x0 as i64 + radius) as i32, (x1 as i64 - radius) as i32))
+                .collect();
+            Band { runs, ..*band }
+        })
+        .collect();
+    let eroded = combine_windows(&narrowed, radius, |window, is_covered| match window.split_first() {
+        Some((first, rest)) if is_covered => rest.iter()
+            .fold(first.runs.clone(), |runs, band| combine_runs(BooleanOperation::Intersection, &runs, &band.runs)),
+        _ => Vec::new(),
+    });
+    // Eroded rows lie within the original ones
+    eroded.unwrap_or_default()
+}
+
+/// Computes the bands of the rows `y` whose runs are the runs of the `bands`
+/// within the window of rows `y − radius..=y + radius`, combined by the `combine` function.
+/// It also gets whether the bands cover the whole window.
+/// 
+/// The bands within the window only change where a band boundary enters or leaves it,
+/// so only these rows are visited. Returns `None` if the result does not fit in `i32` coordinates.
+fn combine_windows(
+    bands: &[Band],
+    radius: i64,
+    combine: impl Fn(&[Band], bool) -> Vec<(i32, i32)>,
+) -> Option<Vec<Band>> {
+    let mut ys: Vec<_> = bands.iter()
+        .flat_map(|band| [band.y0 as i64, band.y1 as i64])
+        .flat_map(|y| [y - radius, y + radius])
+        .collect();
+    ys.sort_unstable();
+    ys.dedup();
+    
+    let mut result = Vec::new();
+    for (&y0, &y1) in ys.iter().zip(ys.iter().skip(1)) {
+        let (top, bottom) = (y0 - radius, y0 + radius + 1);
+        let first = bands.partition_point(|band| band.y1 as i64 <= top);
+        let last = bands.partition_point(|band| (band.y0 as i64) < bottom);
+        let window = &bands[first..last.max(first)];
+        let is_covered = window.first().is_some_and(|band| band.y0 as i64 <= top)
+            && window.last().is_some_and(|band| band.y1 as i64 >= bottom)
+            && window.windows(2).all(|pair| pair[0].y1 == pair[1].y0);
+        let runs = combine(window, is_covered);
+        if !runs.is_empty() {
+            let (y0, y1) = (i32::try_from(y0).ok()?, i32::try_from(y1).ok()?);
+            add_band(&mut result, Band { y0, y1, runs });
+        }
+    }
+    Some(result)
+}
+
+
+// ---------
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+    use euclid::point2;
+    use crate::geometry::{Orthopolygon, orthopolygon_bands};
+    use super::*;
+    
+    /// A 9×7 rect with a 3×1 hole and a dot to the right of it:
+    /// ```
+    /// █████████
+    /// █████████
+    /// █████████
+    /// ███   ███ █
+    /// █████████
+    /// █████████
+    /// █████████
+    /// ```
+    fn shape() -> Vec<Band> {
+        let hole = Orthopolygon::from(vec![point2(6, 3), point2(3, 4)]);
+        let orthopolygons = [Orthopolygon::from(vec![point2(0, 0), point2(9, 7)]), hole, Orthopolygon::from(vec![point2(10, 3), point2(11, 4)])];
+        orthopolygon_bands(orthopolygons.iter())
+    }
+    
+    #[test_case(MorphologicalOperation::Dilation => vec![
+        Band { y0: -1, y1: 2, runs: vec![(-1, 10)] },
+        Band { y0: 2, y1: 5, runs: vec![(-1, 12)] },
+        Band { y0: 5, y1: 8, runs: vec![(-1, 10)] },
+    ])]
+    #[test_case(MorphologicalOperation::Erosion => vec![
+        Band { y0: 1, y1: 2, runs: vec![(1, 8)] },
+        Band { y0: 2, y1: 5, runs: vec![(1, 2), (7, 8)] },
+        Band { y0: 5, y1: 6, runs: vec![(1, 8)] },
+    ])]
+    #[test_case(MorphologicalOperation::Opening => vec![
+        Band { y0: 0, y1: 3, runs: vec![(0, 9)] },
+        Band { y0: 3, y1: 4, runs: vec![(0, 3), (6, 9)] },
+        Band { y0: 4, y1: 7, runs: vec![(0, 9)] },
+    ]; "opening removes the dot")]
+    #[test_case(MorphologicalOperation::Closing => vec![
+        Band { y0: 0, y1: 3, runs: vec![(0, 9)] },
+        Band { y0: 3, y1: 4, runs: vec![(0, 11)] },
+        Band { y0: 4, y1: 7, runs: vec![(0, 9)] },
+    ]; "closing fills the hole and the gap to the dot")]
+    fn test_morph_bands(operation: MorphologicalOperation) -> Vec<Band> {
+        morph_bands(operation, &shape(), 1).unwrap()
+    }
+    
+    #[test]
+    fn test_tall_bands() {
+        let bands = [Band { y0: 0, y1: 1_000_000_000, runs: vec![(0, 10), (12, 20)] }];
+        assert_eq!(morph_bands(MorphologicalOperation::Dilation, &bands, 1), Some(vec![
+            Band { y0: -1, y1: 1_000_000_001, runs: vec![(-1, 21)] },
+        ]));
+        assert_eq!(morph_bands(MorphologicalOperation::Erosion, &bands, 3), Some(vec![
+            Band { y0: 3, y1: 999_999_997, runs: vec![(3, 7), (15, 17)] },
+        ]));
+    }
+    
+    #[test]
+    fn test_dilation_out_of_range() {
+        let bands = [Band { y0: 0, y1: 1, runs: vec![(0, 1)] }];
+        assert_eq!(morph_bands(MorphologicalOperation::Dilation, &bands, u32::MAX), None);
+        assert_eq!(morph_bands(MorphologicalOperation::Erosion, &bands, u32::MAX), Some(vec![]));
+    }
+}
@@ -1,6 +1,9 @@
-use euclid::default::Point2D;
+use euclid::default::{Box2D, Point2D, Vector2D};
 use crate::more_itertools::MoreIterTools;
-use super::polygon::Polygonlike;
+use super::polygon::{Polygon, Polygonlike, Orientation};
+use super::convex_hull::orthopolygon_convex_hull;
+use super::moments::{Moments, CentralMoments};
+use super::chain_codes::{ChainCode, RdCodePoint};
 
 /// Position of a point relative to an orthopolygon.
 pub enum PointPosition {
@@ -19,6 +22,7 @@ pub enum PointPosition {
 /// Warning! Not all same-shaped polygons are equal.
 /// Two `Orthopolygon`s are considered equal only if the have identical vertex lists,
 /// i.e. they have the same shape and also start from the same point and have the same direction.
+/// Use `to_canonical` or `is_same_shape_as` to compare the shapes.
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub struct Orthopolygon {
     even_vertices: Vec<Point2D<i32>>,
@@ -55,6 +59,61 @@ pub trait Orthopolygonlike: Polygonlike<i32> {
         Orthopolygon { even_vertices: self.even_vertices().collect() }
     }
     
+    /// Creates a new orthopolygon of the same shape in the canonical form:
+    /// going in the given `orientation`, without redundant vertices
+    /// (the ones between collinear edges, including zero-length edges),
+    /// and starting from the leftmost of the topmost even vertices.
+    /// 
+    /// Orthopolygons of the same shape have equal canonical forms (and hashes),
+    /// regardless of their start vertex, direction and redundant vertices.
+    /// A degenerate orthopolygon with zero area becomes empty.
+    fn to_canonical(&self, orientation: Orientation) -> Orthopolygon {
+        let mut vertices: Vec<Point2D<i32>> = Vec::new();
+        for vertex in self.vertices() {
+            vertices.push(vertex);
+            while let [.., p0, p1, p2] = vertices[..] && is_redundant(p0, p1, p2) {
+                vertices.remove(vertices.len() - 2);
+            }
+        }
+        // The vertices around the start are checked after the wrap-around
+        while vertices.len() > 2 {
+            let last = vertices.len() - 1;
+            if is_redundant(vertices[last], vertices[0], vertices[1]) {
+                vertices.remove(0);
+            } else if is_redundant(vertices[last - 1], vertices[last], vertices[0]) {
+                vertices.pop();
+            } else {
+                break;
+            }
+        }
+        if vertices.len() < 4 {
+            return Orthopolygon::new(std::iter::empty());
+        }
+        
+        let signed_area: i64 = vertices.iter()
+            .circular_pairs()
+            .map(|(p0, p1)| p0.x as i64 * p1.y as i64 - p1.x as i64 * p0.y as i64)
+            .sum();
+        let is_clockwise = signed_area > 0;
+        if is_clockwise != (orientation == Orientation::Clockwise) {
+            vertices.reverse();
+        }
+        
+        // Now, the edges are alternately horizontal and vertical
+        let start = (0..vertices.len())
+            .filter(|&index| vertices[index].y == vertices[(index + 1) % vertices.len()].y)
+            .min_by_key(|&index| (vertices[index].y, vertices[index].x))
+            .unwrap();
+        vertices.rotate_left(start);
+        Orthopolygon::new(vertices.into_iter().step_by(2))
+    }
+    
+    /// Checks if the orthopolygons have the same shape and position,
+    /// i. e. have equal canonical forms (see `to_canonical`).
+    fn is_same_shape_as(&self, other: &impl Orthopolygonlike) -> bool {
+        self.to_canonical(Orientation::Clockwise) == other.to_canonical(Orientation::Clockwise)
+    }
+    
     /// Uses ray casting algorithm to determine if the point is
     /// inside the orthopolygon, outside, on an edge, or at a vertex.
     fn get_point_position(&self, point: Point2D<i32>) -> PointPosition {
@@ -76,6 +135,180 @@ pub trait Orthopolygonlike: Polygonlike<i32> {
         }
         if intersections % 2 == 0 { PointPosition::Outside } else { PointPosition::Inside }
     }
+    
+    /// Area enclosed by the orthopolygon.
+    /// Positive for clockwise orthopolygons (outer contours)
+    /// and negative for anticlockwise ones (holes).
+    fn signed_area(&self) -> i64 {
+        self.even_vertices()
+            .circular_pairs()
+            .map(|(p0, p1)| (p0.x as i64 - p1.x as i64) * p0.y as i64)
+            .sum()
+    }
+    
+    /// Area enclosed by the orthopolygon regardless of its direction.
+    fn area(&self) -> i64 {
+        self.signed_area().abs()
+    }
+    
+    /// Number of pixels enclosed by the orthopolygon.
+    /// Equals its area, since the vertices are pixel corners.
+    fn pixel_count(&self) -> u64 {
+        self.signed_area().unsigned_abs()
+    }
+    
+    /// Sum of the edge lengths.
+    fn perimeter(&self) -> i64 {
+        self.even_vertices()
+            .circular_pairs()
+            .map(|(p0, p1)| (p1.x as i64 - p0.x as i64).abs() + (p1.y as i64 - p0.y as i64).abs())
+            .sum()
+    }
+    
+    /// The smallest axis-aligned box containing the orthopolygon.
+    fn bounding_box(&self) -> Box2D<i32> {
+        Box2D::from_points(self.even_vertices())
+    }
+    
+    /// Checks if the area enclosed by the orthopolygon (regardless of its direction)
+    /// and the area of the `rect` overlap, i. e. share at least one pixel.
+    fn overlaps_box(&self, rect: Box2D<i32>) -> bool {
+        if rect.is_empty() {
+            return false;
+        }
+        let crosses = |a: i32, b0: i32, b1: i32, min: Point2D<i32>, max: Point2D<i32>|
+            min.y < a && a < max.y && b0.min(b1).max(min.x) < b0.max(b1).min(max.x);
+        let (min, max) = (rect.min, rect.max);
+        let (min_t, max_t) = (min.yx(), max.yx());
+        
+        let mut intersections = 0;
+        for (p0, p1) in self.even_vertices().circular_pairs() {
+            // An edge passing through the rect means an overlap
+            if crosses(p0.y, p0.x, p1.x, min, max) || crosses(p1.x, p0.y, p1.y, min_t, max_t) {
+                return true;
+            }
+            // Ray casting upwards from the center of the pixel `rect.min`
+            if p0.y <= min.y && p0.x.min(p1.x) <= min.x && min.x < p0.x.max(p1.x) {
+                intersections += 1;
+            }
+        }
+        // No edges inside, so the rect is either completely inside or completely outside
+        intersections % 2 == 1
+    }
+    
+    /// Freeman 4-direction chain code of the orthopolygon’s boundary (the “crack code”)
+    /// starting from the first even vertex, see `ChainCode`.
+    fn freeman_code_4(&self) -> ChainCode {
+        ChainCode::crack_code(self.even_vertices())
+    }
+    
+    /// Freeman 8-direction chain code of the pixels on the right side of the boundary:
+    /// the boundary pixels enclosed by a clockwise orthopolygon (an outer contour),
+    /// or the pixels surrounding an anticlockwise one (a hole),
+    /// see `ChainCode::pixel_code`.
+    fn freeman_code_8(&self) -> ChainCode {
+        ChainCode::pixel_code(self.even_vertices())
+    }
+    
+    /// Miyatake’s RD codes of the even vertices, see `RdCodePoint`.
+    fn rd_code(&self) -> Vec<RdCodePoint> {
+        RdCodePoint::of_orthopolygon(self.even_vertices())
+    }
+    
+    /// Creates a new orthopolygon moved by the `offset`.
+    fn translated(&self, offset: Vector2D<i32>) -> Orthopolygon {
+        Orthopolygon::new(self.even_vertices().map(|vertex| vertex + offset))
+    }
+    
+    /// Creates a new orthopolygon rotated by 90° clockwise about the origin
+    /// (in the image coordinates, where the y axis points down).
+    fn rotated_clockwise(&self) -> Orthopolygon {
+        map_orthogonally(self, |p| Point2D::new(-p.y, p.x), true, false)
+    }
+    
+    /// Creates a new orthopolygon rotated by 90° anticlockwise about the origin
+    /// (in the image coordinates, where the y axis points down).
+    fn rotated_anticlockwise(&self) -> Orthopolygon {
+        map_orthogonally(self, |p| Point2D::new(p.y, -p.x), true, false)
+    }
+    
+    /// Creates a new orthopolygon rotated by 180° about the origin.
+    fn rotated_180(&self) -> Orthopolygon {
+        map_orthogonally(self, |p| Point2D::new(-p.x, -p.y), false, false)
+    }
+    
+    /// Creates a new orthopolygon mirrored about the y axis.
+    /// 
+    /// The vertex order is reversed, so that the orthopolygon keeps its direction,
+    /// e. g. a mirrored outer contour is still clockwise.
+    fn flipped_horizontally(&self) -> Orthopolygon {
+        map_orthogonally(self, |p| Point2D::new(-p.x, p.y), false, true)
+    }
+    
+    /// Creates a new orthopolygon mirrored about the x axis,
+    /// e. g. to switch to a coordinate system with the y axis pointing up.
+    /// 
+    /// The vertex order is reversed, so that the orthopolygon keeps its direction,
+    /// e. g. a mirrored outer contour is still clockwise.
+    fn flipped_vertically(&self) -> Orthopolygon {
+        map_orthogonally(self, |p| Point2D::new(p.x, -p.y), false, true)
+    }
+    
+    /// Raw moments of the pixels enclosed by the orthopolygon (regardless of its direction)
+    /// with pixel coordinates taken relative to the `origin`.
+    fn moments_about(&self, origin: Point2D<i32>) -> Moments {
+        let moments = Moments::of_orthopolygon(self.even_vertices(), origin);
+        if moments.m00 < 0 { Moments::default() - moments } else { moments }
+    }
+    
+    /// Raw moments of the pixels enclosed by the orthopolygon.
+    fn raw_moments(&self) -> Moments {
+        self.moments_about(Point2D::zero())
+    }
+    
+    /// Central moments of the pixels enclosed by the orthopolygon.
+    fn central_moments(&self) -> CentralMoments {
+        self.moments_about(self.bounding_box().min).central()
+    }
+    
+    /// Hu’s moment invariants of the pixels enclosed by the orthopolygon.
+    fn hu_moments(&self) -> [f64; 7] {
+        self.central_moments().hu_invariants()
+    }
+    
+    /// Centroid of the area enclosed by the orthopolygon,
+    /// or `None` if the area is zero.
+    fn centroid(&self) -> Option<Point2D<f64>> {
+        let origin = self.bounding_box().min;
+        self.moments_about(origin).mean()
+            .map(|mean| origin.to_f64() + mean + Vector2D::new(0.5, 0.5))
+    }
+}
+
+/// Checks if the vertex `p1` lies on a straight line between `p0` and `p2`
+/// or on a spike going back along the same line.
+fn is_redundant(p0: Point2D<i32>, p1: Point2D<i32>, p2: Point2D<i32>) -> bool {
+    (p0.x == p1.x && p1.x == p2.x) || (p0.y == p1.y && p1.y == p2.y)
+}
+
+/// Applies an axis-preserving `map` to all the vertices of the `orthopolygon`
+/// and chooses the new even vertices so that the even edges are still horizontal.
+/// If the `map` is mirroring, also reverses the vertex order to keep the direction.
+fn map_orthogonally(
+    orthopolygon: &(impl Orthopolygonlike + ?Sized),
+    map: impl Fn(Point2D<i32>) -> Point2D<i32>,
+    swaps_axes: bool,
+    is_mirroring: bool,
+) -> Orthopolygon {
+    let mut vertices: Vec<_> = orthopolygon.vertices().map(map).collect();
+    if is_mirroring && !vertices.is_empty() {
+        // The first vertex stays first, its incoming (odd) edge becomes outgoing
+        vertices[1..].reverse();
+    }
+    // Swapping the axes and reversing both make the odd edges horizontal
+    let start = if swaps_axes != is_mirroring { 1 } else { 0 };
+    let even_vertex_count = vertices.len() / 2;
+    Orthopolygon::new(vertices.into_iter().cycle().skip(start).step_by(2).take(even_vertex_count))
 }
 
 impl Orthopolygonlike for Orthopolygon {
@@ -90,4 +323,132 @@ impl Polygonlike<i32> for Orthopolygon {
             .circular_pairs()
             .flat_map(|(p0, p1)| [p0, Point2D::new(p1.x, p0.y)])
     }
+    
+    fn convex_hull(&self) -> Polygon<i32> {
+        orthopolygon_convex_hull(self.even_vertices())
+    }
+}
+
+
+// ---------
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+    use euclid::{point2, vec2};
+    use super::*;
+    
+    /// ```
+    /// ┌───────┐
+    /// │       │
+    /// │   ┌───┘
+    /// │   │
+    /// └───┘
+    /// ```
+    fn l_shape() -> Orthopolygon {
+        Orthopolygon::from(vec![point2(0, 0), point2(4, 2), point2(2, 4)])
+    }
+    
+    fn reversed(orthopolygon: &Orthopolygon) -> Orthopolygon {
+        let vertices: Vec<_> = orthopolygon.vertices().collect();
+        Orthopolygon::new(vertices.into_iter().rev().step_by(2))
+    }
+    
+    #[test_case(l_shape() => 12)]
+    #[test_case(reversed(&l_shape()) => -12)]
+    #[test_case(Orthopolygon::from(vec![point2(3, 5), point2(4, 6)]) => 1)]
+    #[test_case(Orthopolygon::new(std::iter::empty()) => 0)]
+    fn test_signed_area(orthopolygon: Orthopolygon) -> i64 {
+        orthopolygon.signed_area()
+    }
+    
+    #[test]
+    fn test_measurements() {
+        let shape = l_shape();
+        assert_eq!(shape.area(), 12);
+        assert_eq!(shape.pixel_count(), 12);
+        assert_eq!(shape.perimeter(), 16);
+        assert_eq!(shape.bounding_box(), Box2D::new(point2(0, 0), point2(4, 4)));
+        assert_eq!(reversed(&shape).raw_moments(), shape.raw_moments());
+        assert_eq!(reversed(&shape).perimeter(), 16);
+    }
+    
+    #[test_case(Box2D::new(point2(1, 1), point2(2, 2)) => true; "inside")]
+    #[test_case(Box2D::new(point2(3, 3), point2(5, 5)) => false; "in the notch")]
+    #[test_case(Box2D::new(point2(4, 0), point2(6, 2)) => false; "touching an edge")]
+    #[test_case(Box2D::new(point2(3, 1), point2(6, 2)) => true; "crossing an edge")]
+    #[test_case(Box2D::new(point2(-1, -1), point2(5, 5)) => true; "containing")]
+    #[test_case(Box2D::new(point2(1, 1), point2(1, 3)) => false; "empty")]
+    fn test_overlaps_box(rect: Box2D<i32>) -> bool {
+        assert_eq!(reversed(&l_shape()).overlaps_box(rect), l_shape().overlaps_box(rect));
+        l_shape().overlaps_box(rect)
+    }
+    
+    #[test_case(|o| o.translated(vec2(1, -2)) => vec![point2(1, -2), point2(5, 0), point2(3, 2)]; "translated")]
+    #[test_case(|o| o.rotated_clockwise() => vec![point2(0, 4), point2(-2, 2), point2(-4, 0)]; "rotated clockwise")]
+    #[test_case(|o| o.rotated_anticlockwise() => vec![point2(0, -4), point2(2, -2), point2(4, 0)]; "rotated anticlockwise")]
+    #[test_case(|o| o.rotated_180() => vec![point2(0, 0), point2(-4, -2), point2(-2, -4)]; "rotated 180")]
+    #[test_case(|o| o.flipped_horizontally() => vec![point2(0, 4), point2(-2, 2), point2(-4, 0)]; "flipped horizontally")]
+    #[test_case(|o| o.flipped_vertically() => vec![point2(0, -4), point2(2, -2), point2(4, 0)]; "flipped vertically")]
+    fn test_transforms(transform: fn(&Orthopolygon) -> Orthopolygon) -> Vec<Point2D<i32>> {
+        let transformed = transform(&l_shape());
+        assert_eq!(transformed.signed_area(), 12);
+        assert_eq!(transform(&reversed(&l_shape())).signed_area(), -12);
+        transformed.even_vertices().collect()
+    }
+    
+    #[test_case(l_shape(); "canonical")]
+    #[test_case(reversed(&l_shape()); "reversed")]
+    #[test_case(Orthopolygon::from(vec![point2(4, 2), point2(2, 4), point2(0, 0)]); "shifted start")]
+    #[test_case(Orthopolygon::from(vec![point2(0, 0), point2(2, 0), point2(4, 2), point2(3, 2), point2(2, 3), point2(2, 4)]); "collinear")]
+    #[test_case(Orthopolygon::from(vec![point2(2, 2), point2(2, 4), point2(0, 4), point2(0, 0), point2(4, 2)]); "zero-length edges")]
+    fn test_canonical_form(orthopolygon: Orthopolygon) {
+        assert_eq!(orthopolygon.to_canonical(Orientation::Clockwise), l_shape());
+        assert_eq!(orthopolygon.to_canonical(Orientation::Anticlockwise).signed_area(), -12);
+        assert!(orthopolygon.is_same_shape_as(&l_shape()));
+        assert!(!orthopolygon.is_same_shape_as(&l_shape().translated(vec2(1, 0))));
+    }
+    
+    #[test]
+    fn test_canonical_form_of_anticlockwise_orthopolygon() {
+        let canonical = l_shape().rotated_180().to_canonical(Orientation::Anticlockwise);
+        assert_eq!(canonical.even_vertices().collect::<Vec<_>>(), [point2(0, -4), point2(-2, -2), point2(-4, 0)]);
+    }
+    
+    #[test]
+    fn test_canonical_form_of_degenerate_orthopolygon() {
+        let spike = Orthopolygon::from(vec![point2(0, 0), point2(3, 0)]);
+        assert_eq!(spike.to_canonical(Orientation::Clockwise), Orthopolygon::new(std::iter::empty()));
+    }
+    
+    #[test]
+    fn test_moments_are_pixel_sums() {
+        let shape = l_shape();
+        let pixels: Vec<(i128, i128)> = (0..4).flat_map(|y| (0..4).map(move |x| (x, y)))
+            .filter(|&(x, y)| x < 2 || y < 2)
+            .collect();
+        let sum = |p: u32, q: u32| pixels.iter().map(|&(x, y)| x.pow(p) * y.pow(q)).sum::<i128>();
+        let expected = Moments {
+            m00: sum(0, 0), m10: sum(1, 0), m01: sum(0, 1),
+            m20: sum(2, 0), m11: sum(1, 1), m02: sum(0, 2),
+            m30: sum(3, 0), m21: sum(2, 1), m12: sum(1, 2), m03: sum(0, 3),
+        };
+        assert_eq!(shape.raw_moments(), expected);
+    }
+    
+    #[test]
+    fn test_centroid() {
+        let centroid = l_shape().centroid().unwrap();
+        assert!((centroid - point2(5.0 / 3.0, 5.0 / 3.0)).length() < 1e-12);
+        assert_eq!(Orthopolygon::from(vec![point2(3, 5), point2(4, 6)]).centroid(), Some(point2(3.5, 5.5)));
+        assert_eq!(Orthopolygon::new(std::iter::empty()).centroid(), None);
+    }
+    
+    #[test]
+    fn test_central_moments_do_not_depend_on_position() {
+        let shape = l_shape();
+        let moved = Orthopolygon::new(shape.even_vertices().map(|p| p + vec2(1000, -300)));
+        assert_eq!(moved.central_moments(), shape.central_moments());
+        assert_eq!(moved.hu_moments(), shape.hu_moments());
+    }
 }
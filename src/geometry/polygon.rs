@@ -1,6 +1,25 @@
-use euclid::default::Point2D;
+use euclid::{default::{Box2D, Point2D, Transform2D, Vector2D}, vec2};
 use crate::more_itertools::MoreIterTools;
 use super::Number;
+use super::convex_hull::{convex_hull, OrientedRect};
+
+/// Direction in which the vertices of a polygon go around its area
+/// (in the image coordinates, where the y axis points down).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Clockwise,
+    Anticlockwise,
+}
+
+/// Rule deciding which points are inside a polygon
+/// with self-intersecting or overlapping parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillRule {
+    /// A point is inside if its winding number is odd.
+    EvenOdd,
+    /// A point is inside if its winding number is nonzero.
+    NonZero,
+}
 
 #[derive(Debug)]
 pub struct Polygon<N: Number> {
@@ -15,6 +34,55 @@ impl<N: Number> Polygon<N> {
     pub fn from(vertices: Vec<Point2D<N>>) -> Self {
         Self { vertices }
     }
+    
+    /// Creates a new polygon moved by the `offset`.
+    pub fn translated(&self, offset: Vector2D<N>) -> Self {
+        Self::new(self.vertices().map(|vertex| vertex + offset))
+    }
+    
+    /// Creates a new polygon scaled about the origin
+    /// by `x_factor` along the x axis and by `y_factor` along the y axis.
+    /// 
+    /// A negative factor mirrors the polygon and thus reverses its orientation.
+    pub fn scaled(&self, x_factor: N, y_factor: N) -> Self {
+        Self::new(self.vertices().map(|vertex| Point2D::new(vertex.x * x_factor, vertex.y * y_factor)))
+    }
+    
+    /// Creates a new polygon with all the vertices transformed by the affine `transform`.
+    /// 
+    /// A mirroring transform (with a negative determinant) reverses the orientation.
+    pub fn transformed(&self, transform: &Transform2D<N>) -> Self {
+        Self::new(self.vertices().map(|vertex| transform.transform_point(vertex)))
+    }
+    
+    /// Area enclosed by the polygon calculated by the shoelace formula.
+    /// Positive for clockwise polygons and negative for anticlockwise ones.
+    /// Parts of a self-intersecting polygon are counted with their winding numbers.
+    pub fn signed_area(&self) -> f64 {
+        shoelace_area(self.edges())
+    }
+    
+    /// Centroid of the area enclosed by the polygon,
+    /// or `None` if the area is zero.
+    pub fn centroid(&self) -> Option<Point2D<f64>> {
+        // The vertices are taken relative to the first one to reduce rounding errors
+        let origin = self.vertices().next()?.to_f64();
+        let (doubled_area, x_sum, y_sum) = self.edges()
+            .map(|(p0, p1)| (p0.to_f64() - origin, p1.to_f64() - origin))
+            .fold((0.0, 0.0, 0.0), |(area, x, y), (p0, p1)| {
+                let cross = p0.cross(p1);
+                (area + cross, x + (p0.x + p1.x) * cross, y + (p0.y + p1.y) * cross)
+            });
+        if doubled_area == 0.0 {
+            return None;
+        }
+        Some(origin + vec2(x_sum, y_sum) / (3.0 * doubled_area))
+    }
+    
+    /// The smallest axis-aligned box containing the polygon.
+    pub fn bounding_box(&self) -> Box2D<N> {
+        Box2D::from_points(self.vertices())
+    }
 }
 
 pub trait Polygonlike<N: Number> {
@@ -30,6 +98,87 @@ pub trait Polygonlike<N: Number> {
         let vertices: Vec<Point2D<NDest>> = self.vertices().map(|v| v.cast()).collect();
         Polygon::from(vertices)
     }
+    
+    /// Creates a new polygon with the same vertices in the reverse order,
+    /// i. e. with the opposite orientation.
+    fn reversed(&self) -> Polygon<N> {
+        let mut vertices: Vec<_> = self.vertices().collect();
+        vertices.reverse();
+        Polygon::from(vertices)
+    }
+    
+    /// Orientation of the polygon by the sign of its area,
+    /// or `None` if the area is zero.
+    fn orientation(&self) -> Option<Orientation> {
+        let signed_area = shoelace_area(self.edges());
+        if signed_area > 0.0 {
+            Some(Orientation::Clockwise)
+        } else if signed_area < 0.0 {
+            Some(Orientation::Anticlockwise)
+        } else {
+            None
+        }
+    }
+    
+    /// Convex hull of the polygon’s vertices, clockwise,
+    /// starting from the leftmost of the topmost vertices.
+    /// Collinear vertices on the hull edges are omitted.
+    fn convex_hull(&self) -> Polygon<N> {
+        Polygon::from(convex_hull(self.vertices()))
+    }
+    
+    /// The minimum-area rectangle of any orientation enclosing the polygon,
+    /// or `None` if the polygon has zero area.
+    fn min_area_rect(&self) -> Option<OrientedRect> {
+        let hull: Vec<_> = self.convex_hull().vertices().map(|vertex| vertex.to_f64()).collect();
+        OrientedRect::enclosing(&hull)
+    }
+    
+    /// Ratio of the area of the polygon to the area of its convex hull,
+    /// or `None` if the hull area is zero.
+    fn solidity(&self) -> Option<f64> {
+        let hull_area = self.convex_hull().signed_area();
+        (hull_area > 0.0).then(|| shoelace_area(self.edges()).abs() / hull_area)
+    }
+    
+    /// Ratio of the area of the polygon to the area of its minimum-area rectangle,
+    /// or `None` if the area is zero.
+    fn rectangularity(&self) -> Option<f64> {
+        self.min_area_rect().map(|rect| shoelace_area(self.edges()).abs() / rect.area())
+    }
+    
+    /// Number of times the polygon winds around the `point`.
+    /// Positive if it goes clockwise and negative if anticlockwise.
+    /// 
+    /// A point on an edge is counted as if it were moved slightly to the right
+    /// (and, on a horizontal edge, slightly down), so that, e. g., the left and top edges
+    /// of a box are inside it, and the right and bottom ones are outside.
+    /// Thus, the polygons sharing an edge never both contain its points.
+    fn winding_number(&self, point: Point2D<N>) -> i32 {
+        let point = point.to_f64();
+        let mut winding_number = 0;
+        for (p0, p1) in self.edges() {
+            let (p0, p1) = (p0.to_f64(), p1.to_f64());
+            // The side of the edge the point is on
+            let side = (p1 - p0).cross(point - p0);
+            if p0.y <= point.y && point.y < p1.y && side > 0.0 {
+                winding_number += 1;
+            } else if p1.y <= point.y && point.y < p0.y && side < 0.0 {
+                winding_number -= 1;
+            }
+        }
+        winding_number
+    }
+    
+    /// Checks if the `point` is inside the polygon by the `fill_rule`.
+    /// See `winding_number` for the points on the edges.
+    fn contains(&self, point: Point2D<N>, fill_rule: FillRule) -> bool {
+        let winding_number = self.winding_number(point);
+        match fill_rule {
+            FillRule::EvenOdd => winding_number % 2 != 0,
+            FillRule::NonZero => winding_number != 0,
+        }
+    }
 }
 
 impl<N: Number> Polygonlike<N> for Polygon<N> {
@@ -37,3 +186,106 @@ impl<N: Number> Polygonlike<N> for Polygon<N> {
         self.vertices.iter().cloned()
     }
 }
+
+/// Area enclosed by the `edges` calculated by the shoelace formula,
+/// see `Polygon::signed_area`.
+fn shoelace_area<N: Number>(edges: impl Iterator<Item = (Point2D<N>, Point2D<N>)>) -> f64 {
+    edges
+        .map(|(p0, p1)| p0.to_f64().to_vector().cross(p1.to_f64().to_vector()))
+        .sum::<f64>() / 2.0
+}
+
+
+// ---------
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+    use euclid::point2;
+    use super::*;
+    
+    /// Two overlapping squares joined by a bridge traversed in both directions,
+    /// so that their common part is wound twice.
+    /// ```
+    /// ┌───┐
+    /// │ ┌─┼─┐
+    /// └─┼─┘ │
+    ///   └───┘
+    /// ```
+    fn double_square() -> Polygon<f64> {
+        Polygon::from(vec![
+            point2(0.0, 0.0), point2(2.0, 0.0), point2(2.0, 2.0), point2(0.0, 2.0), point2(0.0, 0.0),
+            point2(1.0, 1.0), point2(3.0, 1.0), point2(3.0, 3.0), point2(1.0, 3.0), point2(1.0, 1.0),
+        ])
+    }
+    
+    fn triangle() -> Polygon<f64> {
+        Polygon::from(vec![point2(0.0, 0.0), point2(3.0, 0.0), point2(0.0, 3.0)])
+    }
+    
+    #[test]
+    fn test_measurements() {
+        let shape = triangle();
+        assert_eq!(shape.signed_area(), 4.5);
+        assert_eq!(shape.orientation(), Some(Orientation::Clockwise));
+        assert_eq!(shape.centroid(), Some(point2(1.0, 1.0)));
+        assert_eq!(shape.bounding_box(), Box2D::new(point2(0.0, 0.0), point2(3.0, 3.0)));
+        
+        let reversed = shape.reversed();
+        assert_eq!(reversed.signed_area(), -4.5);
+        assert_eq!(reversed.orientation(), Some(Orientation::Anticlockwise));
+        assert_eq!(reversed.centroid(), Some(point2(1.0, 1.0)));
+    }
+    
+    #[test]
+    fn test_degenerate_polygons() {
+        let segment = Polygon::from(vec![point2(0.0, 0.0), point2(2.0, 2.0)]);
+        assert_eq!(segment.signed_area(), 0.0);
+        assert_eq!(segment.orientation(), None);
+        assert_eq!(segment.centroid(), None);
+        assert!(!segment.contains(point2(1.0, 1.0), FillRule::NonZero));
+        
+        let empty = Polygon::<f64>::new(std::iter::empty());
+        assert_eq!(empty.signed_area(), 0.0);
+        assert_eq!(empty.centroid(), None);
+    }
+    
+    #[test]
+    fn test_centroid_far_from_origin() {
+        let offset = euclid::vec2(1e9, -1e9);
+        let moved = Polygon::new(triangle().vertices().map(|v| v + offset));
+        let centroid = moved.centroid().unwrap();
+        assert!((centroid - (point2(1.0, 1.0) + offset)).length() < 1e-6);
+    }
+    
+    #[test]
+    fn test_transforms() {
+        let expected = vec![point2(-2.0, 2.0), point2(-2.0, 5.0), point2(-5.0, 2.0)];
+        let rotation = Transform2D::rotation(euclid::Angle::frac_pi_2()).then_translate(vec2(-2.0, 2.0));
+        let rotated = triangle().transformed(&rotation);
+        assert!(rotated.vertices().zip(expected).all(|(actual, expected)| (actual - expected).length() < 1e-12));
+        assert_eq!(rotated.orientation(), Some(Orientation::Clockwise));
+        
+        let flipped = triangle().scaled(1.0, -1.0).translated(vec2(0.0, 3.0));
+        assert_eq!(flipped.vertices().collect::<Vec<_>>(), [point2(0.0, 3.0), point2(3.0, 3.0), point2(0.0, 0.0)]);
+        assert_eq!(flipped.orientation(), Some(Orientation::Anticlockwise));
+    }
+    
+    #[test_case(point2(0.5, 1.5) => (1, true, true); "single")]
+    #[test_case(point2(1.5, 1.5) => (2, false, true); "double")]
+    #[test_case(point2(2.5, 0.5) => (0, false, false); "outside")]
+    #[test_case(point2(0.0, 1.5) => (1, true, true); "left edge")]
+    #[test_case(point2(2.0, 0.5) => (0, false, false); "right edge")]
+    #[test_case(point2(1.5, 0.0) => (1, true, true); "top edge")]
+    #[test_case(point2(0.5, 2.0) => (0, false, false); "bottom edge")]
+    fn test_containment(point: Point2D<f64>) -> (i32, bool, bool) {
+        let shape = double_square();
+        assert_eq!(shape.reversed().winding_number(point), -shape.winding_number(point));
+        (shape.winding_number(point), shape.contains(point, FillRule::EvenOdd), shape.contains(point, FillRule::NonZero))
+    }
+    
+    #[test]
+    fn test_signed_area_counts_winding() {
+        assert_eq!(double_square().signed_area(), 8.0);
+    }
+}
@@ -1,4 +1,3 @@
-use std::collections::BTreeSet;
 use itertools::Itertools;
 use image::{GrayImage, Luma};
 use crate::more_itertools::MoreIterTools;
@@ -9,49 +8,83 @@ use super::Orthopolygonlike;
 /// The value of each pixel of the canvas inside an orthopolygon
 /// (more precisely, by the even-odd rule)
 /// is modified using the `draw_pixel` function.
+/// The parts of the orthopolygons outside the canvas are clipped.
 pub fn draw_orthopolygons<'a, Ortho>(
     canvas: &mut GrayImage,
     draw_pixel: impl Fn(u8) -> u8,
     orthopolygons: impl Iterator<Item = &'a Ortho>,
+) where Ortho: Orthopolygonlike + 'a {
+    let height = i32::try_from(canvas.height()).unwrap_or(i32::MAX);
+    scan_orthopolygons(orthopolygons, |y0, y1, crossings| {
+        fill_band(y0.max(0), y1.min(height), crossings, |y, x0, x1| draw_horizontal_line(canvas, &draw_pixel, y, x0, x1));
+    });
+}
+
+/// Scans one or more `orthopolygons` row by row
+/// and calls `fill_span(y, x0, x1)` for each span of pixels `x0..x1` in the row `y`
+/// inside an orthopolygon (more precisely, by the even-odd rule).
+/// 
+/// The rows are scanned from top to bottom, the spans in each row from left to right.
+pub fn fill_orthopolygons<'a, Ortho>(
+    orthopolygons: impl Iterator<Item = &'a Ortho>,
+    mut fill_span: impl FnMut(i32, i32, i32),
+) where Ortho: Orthopolygonlike + 'a {
+    scan_orthopolygons(orthopolygons, |y0, y1, crossings| fill_band(y0, y1, crossings, &mut fill_span));
+}
+
+/// Calls `fill_span` for the spans between pairs of `crossings` in each row of the band `y0..y1`.
+fn fill_band(y0: i32, y1: i32, crossings: &[(i32, i32)], mut fill_span: impl FnMut(i32, i32, i32)) {
+    debug_assert!(crossings.len().is_multiple_of(2));
+    for y in y0..y1 {
+        for (&(x0, _), &(x1, _)) in crossings.iter().tuples() {
+            if x0 < x1 {
+                fill_span(y, x0, x1);
+            }
+        }
+    }
+}
+
+/// Scans one or more `orthopolygons` in horizontal bands, where no edges start or end,
+/// and calls `scan_band(y0, y1, crossings)` for each band `y0..y1` from top to bottom.
+/// 
+/// `crossings` are the vertical edges crossing the band, sorted by x.
+/// Each one is given as its x coordinate and its direction:
+/// 1 if it goes up (e. g. the left edge of a clockwise orthopolygon) and −1 if it goes down.
+pub fn scan_orthopolygons<'a, Ortho>(
+    orthopolygons: impl Iterator<Item = &'a Ortho>,
+    mut scan_band: impl FnMut(i32, i32, &[(i32, i32)]),
 ) where Ortho: Orthopolygonlike + 'a {
     let edges: Vec<_> = orthopolygons
         .flat_map(|p| p.even_vertices().circular_pairs())
-        .map(|(u, v)| if u.y < v.y { (u.y, v.x, v.y) } else { (v.y, v.x, u.y) })
+        .filter(|(u, v)| u.y != v.y)
+        .map(|(u, v)| if u.y < v.y { (u.y, v.x, v.y, -1) } else { (v.y, v.x, u.y, 1) })
         .sorted()
         .collect();
     
-    let mut parity = false;
     let mut edge_index = 0;
-    let mut active_edges = BTreeSet::new();
-    let Some(&(mut y, _, _)) = edges.iter().next() else { return };
+    // Sorted by x, coinciding edges of different orthopolygons are all kept
+    let mut active_edges: Vec<(i32, i32, i32)> = Vec::new();
+    let mut crossings = Vec::new();
+    let Some(&(mut y, _, _, _)) = edges.first() else { return };
     
     while edge_index < edges.len() || !active_edges.is_empty() {
-        debug_assert!(!parity);
-        
-        while edge_index < edges.len() {
-            let (y0, x, y1) = edges[edge_index];
-            if y0 == y {
-                active_edges.insert((x, y1));
-                edge_index += 1;
-            } else {
-                break;
-            }
+        while edge_index < edges.len() && edges[edge_index].0 == y {
+            let (_, x, y1, direction) = edges[edge_index];
+            let position = active_edges.partition_point(|&(active_x, _, _)| active_x <= x);
+            active_edges.insert(position, (x, y1, direction));
+            edge_index += 1;
         }
         
-        let mut prev_x = i32::MIN;
-        active_edges.retain(|&(x, y1)|
-            if y1 > y {
-                if parity {
-                    draw_horizontal_line(canvas, &draw_pixel, y, prev_x, x);
-                }
-                parity = !parity;
-                prev_x = x;
-                true
-            } else {
-                false
-            }
-        );
-        y += 1;
+        let next_start = edges.get(edge_index).map_or(i32::MAX, |edge| edge.0);
+        let next_end = active_edges.iter().map(|&(_, y1, _)| y1).min().unwrap_or(i32::MAX);
+        let next_y = next_start.min(next_end);
+        if !active_edges.is_empty() {
+            crossings.clear();
+            crossings.extend(active_edges.iter().map(|&(x, _, direction)| (x, direction)));
+            scan_band(y, next_y, &crossings);
+        }
+        active_edges.retain(|&(_, y1, _)| y1 > next_y);
+        y = next_y;
     }
 }
 
@@ -60,8 +93,51 @@ fn draw_horizontal_line(
     draw_pixel: impl Fn(u8) -> u8,
     y: i32, x0: i32, x1: i32,
 ) {
+    let (width, height) = canvas.dimensions();
+    if y < 0 || y as i64 >= height as i64 {
+        return;
+    }
+    let x0 = x0.max(0) as i64;
+    let x1 = (x1 as i64).min(width as i64);
     for x in x0..x1 {
         let Luma([value]) = canvas.get_pixel_mut(x as u32, y as u32);
         *value = draw_pixel(*value);
     }
 }
+
+
+// ---------
+
+#[cfg(test)]
+mod tests {
+    use euclid::point2;
+    use crate::geometry::Orthopolygon;
+    use super::*;
+    
+    #[test]
+    fn test_clipping() {
+        let orthopolygons = [
+            Orthopolygon::from(vec![point2(-2, -3), point2(2, 1)]),
+            Orthopolygon::from(vec![point2(1, 2), point2(9, 9)]),
+        ];
+        let mut canvas = GrayImage::new(3, 3);
+        draw_orthopolygons(&mut canvas, |_| 1, orthopolygons.iter());
+        assert_eq!(canvas.into_raw(), [
+            1, 1, 0,
+            0, 0, 0,
+            0, 1, 1,
+        ]);
+    }
+    
+    #[test]
+    fn test_clipping_of_tall_orthopolygon() {
+        // Would take billions of steps if the rows outside the canvas were visited
+        let orthopolygons = [Orthopolygon::from(vec![point2(1, i32::MIN), point2(2, i32::MAX)])];
+        let mut canvas = GrayImage::new(3, 2);
+        draw_orthopolygons(&mut canvas, |_| 1, orthopolygons.iter());
+        assert_eq!(canvas.into_raw(), [
+            0, 1, 0,
+            0, 1, 0,
+        ]);
+    }
+}
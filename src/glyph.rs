@@ -1,12 +1,14 @@
 use std::iter;
 use euclid::default::{Point2D, Size2D};
-use crate::geometry::{Orthopolygonlike, Orthopolygon};
+use crate::geometry::{Orthopolygonlike, Orthopolygon, Orientation};
 use crate::image_contour_collection::Contour;
 
 /// A _glyph_ is a contour, possibly, with holes, that does not remember its location.
 /// The upper-left corner of a glyph’s bounding box always has the coordinates (0, 0).
 /// 
-/// Implements `Eq` and `Hash`. Two glyphs of the same shape are considered equal.
+/// Implements `Eq` and `Hash`. Two glyphs of the same shape are considered equal,
+/// since the contours are stored in the canonical form (see `Orthopolygonlike::to_canonical`),
+/// the outer contour clockwise and the holes anticlockwise, sorted by their vertices.
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub struct Glyph {
     size: Size2D<i32>,
@@ -46,7 +48,18 @@ impl Glyph {
             })
             .collect();
         
-        (Self { size, contours }, location)
+        (Self::from_contours(size, contours), location)
+    }
+    
+    /// Creates a glyph from its contours, the outer one first,
+    /// bringing them to the canonical form.
+    fn from_contours(size: Size2D<i32>, mut contours: Vec<Orthopolygon>) -> Self {
+        contours[0] = contours[0].to_canonical(Orientation::Clockwise);
+        for hole in &mut contours[1..] {
+            *hole = hole.to_canonical(Orientation::Anticlockwise);
+        }
+        contours[1..].sort_by(|a, b| a.even_vertices().map(|p| (p.y, p.x)).cmp(b.even_vertices().map(|p| (p.y, p.x))));
+        Self { size, contours }
     }
     
     /// Width and height of the glyph’s bounding box.
@@ -68,4 +81,81 @@ impl Glyph {
     pub fn inner_contours(&self) -> &[Orthopolygon] {
         &self.contours[1..]
     }
+    
+    /// Creates a new glyph rotated by 90° clockwise.
+    pub fn rotated_clockwise(&self) -> Self {
+        self.transformed(|contour| contour.rotated_clockwise(), true)
+    }
+    
+    /// Creates a new glyph rotated by 90° anticlockwise.
+    pub fn rotated_anticlockwise(&self) -> Self {
+        self.transformed(|contour| contour.rotated_anticlockwise(), true)
+    }
+    
+    /// Creates a new glyph rotated by 180°.
+    pub fn rotated_180(&self) -> Self {
+        self.transformed(|contour| contour.rotated_180(), false)
+    }
+    
+    /// Creates a new glyph mirrored left to right.
+    pub fn flipped_horizontally(&self) -> Self {
+        self.transformed(|contour| contour.flipped_horizontally(), false)
+    }
+    
+    /// Creates a new glyph mirrored upside down.
+    pub fn flipped_vertically(&self) -> Self {
+        self.transformed(|contour| contour.flipped_vertically(), false)
+    }
+    
+    /// Applies an axis-preserving `transform` to all the contours
+    /// and moves the result back, so that its upper-left corner is at (0, 0).
+    /// Glyphs do not remember their location, so no translation is needed.
+    fn transformed(&self, transform: impl Fn(&Orthopolygon) -> Orthopolygon, swaps_axes: bool) -> Self {
+        let contours: Vec<_> = self.contours.iter().map(transform).collect();
+        let offset = -contours[0].bounding_box().min.to_vector();
+        let contours = contours.iter().map(|contour| contour.translated(offset)).collect();
+        let size = if swaps_axes { Size2D::new(self.size.height, self.size.width) } else { self.size };
+        Self::from_contours(size, contours)
+    }
+}
+
+
+// ---------
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use image::{imageops, GrayImage};
+    use test_case::test_case;
+    use crate::image_contour_collection::{ImageContourCollection, Connectivity};
+    use crate::test_images::get_test_image;
+    use super::*;
+    
+    fn get_glyphs(image: &GrayImage) -> HashSet<Glyph> {
+        let contour_collection = ImageContourCollection::new(image, false, Connectivity::Eight);
+        contour_collection.outer_contours().map(|contour| Glyph::from_contour(contour).0).collect()
+    }
+    
+    #[test_case(Glyph::rotated_clockwise, imageops::rotate90; "rotated clockwise")]
+    #[test_case(Glyph::rotated_anticlockwise, imageops::rotate270; "rotated anticlockwise")]
+    #[test_case(Glyph::rotated_180, imageops::rotate180; "rotated 180")]
+    #[test_case(Glyph::flipped_horizontally, imageops::flip_horizontal; "flipped horizontally")]
+    #[test_case(Glyph::flipped_vertically, imageops::flip_vertical; "flipped vertically")]
+    fn test_transformed_glyphs_equal_glyphs_of_transformed_image(transform: fn(&Glyph) -> Glyph, transform_image: fn(&GrayImage) -> GrayImage) {
+        for name in ["art_50x50_dragon", "text_142x64_theos"] {
+            let image = get_test_image(name).unwrap();
+            let glyphs = get_glyphs(&image);
+            let transformed_glyphs: HashSet<_> = glyphs.iter().map(transform).collect();
+            assert!(transformed_glyphs == get_glyphs(&transform_image(&image)), "{name}: transformed glyphs differ");
+        }
+    }
+    
+    #[test]
+    fn test_glyph_rotated_back_is_equal() {
+        let image = get_test_image("text_142x64_theos").unwrap();
+        for glyph in get_glyphs(&image) {
+            assert_eq!(glyph.rotated_clockwise().rotated_anticlockwise(), glyph);
+            assert_eq!(glyph.flipped_vertically().rotated_180().flipped_horizontally(), glyph);
+        }
+    }
 }
@@ -5,17 +5,49 @@ mod feature_automaton;
 mod point_list_builder;
 mod hierarchy_builder;
 mod contours;
+mod binarization;
+mod streaming_builder;
+mod errors;
+mod validation;
+mod filtering;
+mod spatial_index;
+mod rasterization;
+mod serialization;
+mod row_runs;
+mod morphology;
 #[cfg(test)] mod tests;
 
-use std::iter;
-use image::GrayImage;
-use contour_collection_builder::ContourCollectionBuilder;
+use std::thread;
+use itertools::Itertools;
+use euclid::default::Box2D;
+use image::{GenericImageView, GrayImage, Luma, Pixel, SubImage, math::Rect};
+use crate::geometry::{Orthopolygonlike, BooleanOperation, MorphologicalOperation, orthopolygon_bands, combine_bands, morph_bands};
 use hierarchy_builder::HierarchyItem;
 use point_list_builder::PointListItem;
-use row_changes::RowChangeIter;
-use row_pair_changes::RowPairChangeIter;
 use contours::{ChildContourIter, DescendantContourIter};
+use binarization::{threshold_rows, otsu_threshold, AdaptiveRowIter};
+use row_runs::{RowRuns, trace_bands};
 pub use contours::Contour;
+pub use binarization::Binarization;
+pub use streaming_builder::{StreamingBuilder, InvalidRowError};
+pub use errors::{BuildError, ValidationError, LoadError};
+pub use spatial_index::ContourIndex;
+
+/// Pixel connectivity of the foreground.
+/// 
+/// The background always has the opposite connectivity,
+/// so that the contours never cross each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Connectivity {
+    /// Diagonally adjacent foreground pixels belong to the same contour.
+    /// The outer contours are 8-connected, the holes are 4-connected.
+    #[default]
+    Eight,
+    /// Only horizontally and vertically adjacent foreground pixels
+    /// belong to the same contour.
+    /// The outer contours are 4-connected, the holes are 8-connected.
+    Four,
+}
 
 /// Finds contours in a binary image.
 /// 
@@ -25,29 +57,34 @@ pub use contours::Contour;
 /// The upper-left corner of the pixel with coordinates (x, y) has coordinates (x, y),
 /// and its lower-right corner has coordinates  (x + 1, y + 1).
 /// 
-/// The outer contours are 8-connected and clockwise.
-/// The inner contours (holes) are 4-connected and anti-clockwise.
+/// The outer contours are clockwise.
+/// The inner contours (holes) are anti-clockwise.
+/// By default, the outer contours are 8-connected and the holes are 4-connected
+/// (see `Connectivity`).
 /// 
 /// [^1]: Takafumi Miyatake, Hitoshi Matsushima, Masakazu Ejiri, 1997:
 /// _Contour representation of binary images using run-type direction codes_.
 pub struct ImageContourCollection {
+    left: i32,
+    top: i32,
     width: i32,
     height: i32,
+    connectivity: Connectivity,
     hierarchy: Vec<HierarchyItem>,
     pub point_list: Vec<PointListItem>,
 }
 
 impl ImageContourCollection {
-    /// An alias for `new(image, inverted: false)`.
+    /// An alias for `new(image, inverted: false, Connectivity::Eight)`.
     /// Assumes black background and white foreground.
     pub fn white_on_black(image: &GrayImage) -> Self {
-        Self::new(image, false)
+        Self::new(image, false, Connectivity::Eight)
     }
     
-    /// An alias for `new(image, inverted: true)`.
+    /// An alias for `new(image, inverted: true, Connectivity::Eight)`.
     /// Assumes white background and black foreground.
     pub fn black_on_white(image: &GrayImage) -> Self {
-        Self::new(image, true)
+        Self::new(image, true, Connectivity::Eight)
     }
     
     /// Creates a new instance of `ImageContourCollection`
@@ -57,50 +94,382 @@ impl ImageContourCollection {
     /// 
     /// Although `image` is an 8-bit grayscale,
     /// the algorithm considers all non-zero pixel values as white.
-    /// So the image should be binarized beforehand.
+    /// So the image should be binarized beforehand,
+    /// or `from_image` should be used instead.
     /// 
     /// If `inverted` is `true`, black pixels will be considered as foreground
     /// instead of white ones.
-    pub fn new(image: &GrayImage, inverted: bool) -> Self {
+    /// 
+    /// `connectivity` determines which foreground pixels belong to the same contour.
+    /// 
+    /// Panics if the collection cannot be built, see `try_new`.
+    pub fn new(image: &GrayImage, inverted: bool, connectivity: Connectivity) -> Self {
+        Self::try_new(image, inverted, connectivity).unwrap_or_else(|error| panic!("{error}"))
+    }
+    
+    /// Works like `new`, but returns an error instead of panicking,
+    /// e. g. if the image is too large for `i32` coordinates.
+    pub fn try_new(image: &GrayImage, inverted: bool, connectivity: Connectivity) -> Result<Self, BuildError> {
+        let rows = image.rows()
+            .map(|row| row.map(|&Luma([value])| value != 0));
+        Self::from_binary_rows(image.dimensions(), inverted, connectivity, rows)
+    }
+    
+    /// Works like `new`, but splits the image into `strip_count` horizontal strips
+    /// and traces them in parallel threads.
+    /// The open contour ends and contour merges are then stitched across the seams.
+    /// 
+    /// The result is identical to the one of `new`.
+    /// A reasonable `strip_count` is `std::thread::available_parallelism()`.
+    pub fn new_parallel(image: &GrayImage, inverted: bool, connectivity: Connectivity, strip_count: usize) -> Self {
+        Self::try_new_parallel(image, inverted, connectivity, strip_count).unwrap_or_else(|error| panic!("{error}"))
+    }
+    
+    /// Works like `new_parallel`, but returns an error instead of panicking.
+    pub fn try_new_parallel(image: &GrayImage, inverted: bool, connectivity: Connectivity, strip_count: usize) -> Result<Self, BuildError> {
         let (width, height) = image.dimensions();
-        let mut builder = ContourCollectionBuilder::new(width as i32, height as i32);
-        
-        // Row changes are stored in two buffers of fixed capacity to avoid allocation
-        let capacity = width as usize + 2;
-        let mut top_changes = Vec::with_capacity(capacity);
-        let mut bottom_changes = Vec::with_capacity(capacity);
+        check_dimensions(width, height)?;
+        let strip_count = strip_count.clamp(1, height.max(1) as usize);
+        let row = |y: u32| {
+            let start = y as usize * width as usize;
+            image.as_raw()[start..start + width as usize].iter().map(|&value| value != 0)
+        };
         
-        // Add padding rows to the top and the bottom of the image
-        bottom_changes.extend(RowChangeIter::empty());
-        let rows = image.rows()
-            .map(|row| RowChangeIter::from(row, inverted))
-            .chain(iter::once(RowChangeIter::empty()));
+        let strips: Vec<_> = thread::scope(|scope| {
+            let threads: Vec<_> = (0..=strip_count)
+                .map(|index| (index as u64 * height as u64 / strip_count as u64) as u32)
+                .tuple_windows()
+                .map(|(top, bottom)| scope.spawn(move || {
+                    let mut builder = if top == 0 {
+                        StreamingBuilder::new(width, inverted, connectivity)
+                    } else {
+                        StreamingBuilder::new_strip(width, inverted, connectivity, top, row(top - 1))
+                    };
+                    for y in top..bottom {
+                        builder.push_binary_pixels(row(y));
+                    }
+                    builder.into_strip(bottom == height)
+                }))
+                .collect();
+            threads.into_iter().map(|thread| thread.join().unwrap()).collect()
+        });
         
-        // Scan the image row by row, top to bottom
-        for (row_index, row_changes) in rows.enumerate() {
-            // Swap the buffers so the old bottom row becomes the new top row.
-            // Fill new bottom row with new row changes
-            (top_changes, bottom_changes) = (bottom_changes, top_changes);
-            bottom_changes.clear();
-            bottom_changes.extend(row_changes);
-            
-            // Look for changes in two adjacent image rows
-            let row_pair_changes = RowPairChangeIter::new(&top_changes, &bottom_changes);
-            
-            for change in row_pair_changes {
-                // Update the point list and contour hierarchy
-                builder.add_row_pair_change(row_index as i32, change);
-            }
+        // Stitch the strips top to bottom
+        let mut strips = strips.into_iter();
+        let mut builder = strips.next().unwrap();
+        for strip in strips {
+            builder.append_strip(strip);
+        }
+        builder.into(width as i32, height as i32)
+    }
+    
+    /// Works like `new`, but traces only the part of an image seen through
+    /// the `sub_image` view (e. g. `image.view(x, y, width, height)`) without copying it.
+    /// 
+    /// The pixels outside the view are considered background,
+    /// so the contours crossing the view border are cut by it.
+    /// The contour points are given in the coordinates of the whole image.
+    /// `origin` and `dimensions` return the position and the size of the view.
+    pub fn from_sub_image(sub_image: &SubImage<&GrayImage>, inverted: bool, connectivity: Connectivity) -> Self {
+        Self::try_from_sub_image(sub_image, inverted, connectivity).unwrap_or_else(|error| panic!("{error}"))
+    }
+    
+    /// Works like `from_sub_image`, but returns an error instead of panicking.
+    pub fn try_from_sub_image(sub_image: &SubImage<&GrayImage>, inverted: bool, connectivity: Connectivity) -> Result<Self, BuildError> {
+        let image = sub_image.inner();
+        let (left, top) = sub_image.offsets();
+        let (width, height) = sub_image.dimensions();
+        check_dimensions(left as u64 + width as u64, top as u64 + height as u64)?;
+        let rows = (top..top + height).map(|y| {
+            let start = y as usize * image.width() as usize + left as usize;
+            image.as_raw()[start..start + width as usize].iter().map(|&value| value != 0)
+        });
+        Ok(Self::from_binary_rows((width, height), inverted, connectivity, rows)?
+            .moved_to(left as i32, top as i32))
+    }
+    
+    /// Works like `from_sub_image` for the region of the `image` given by `rect`.
+    /// 
+    /// Panics if `rect` does not fit in the image.
+    pub fn new_in_rect(image: &GrayImage, rect: Rect, inverted: bool, connectivity: Connectivity) -> Self {
+        Self::try_new_in_rect(image, rect, inverted, connectivity).unwrap_or_else(|error| panic!("{error}"))
+    }
+    
+    /// Works like `new_in_rect`, but returns an error instead of panicking.
+    pub fn try_new_in_rect(image: &GrayImage, rect: Rect, inverted: bool, connectivity: Connectivity) -> Result<Self, BuildError> {
+        let fits_horizontally = rect.x as u64 + rect.width as u64 <= image.width() as u64;
+        let fits_vertically = rect.y as u64 + rect.height as u64 <= image.height() as u64;
+        if !fits_horizontally || !fits_vertically {
+            return Err(BuildError::RectOutOfBounds { rect });
+        }
+        let sub_image = image.view(rect.x, rect.y, rect.width, rect.height);
+        Self::try_from_sub_image(&sub_image, inverted, connectivity)
+    }
+    
+    /// Creates a new instance of `ImageContourCollection`
+    /// containing contours of a grayscale or color `image`
+    /// (e. g. `DynamicImage` or any other `GenericImageView`).
+    /// 
+    /// The image is binarized on the fly using the given `binarization` method,
+    /// no intermediate binary image is created.
+    /// 
+    /// `inverted` and `connectivity` have the same meaning as in `new`.
+    pub fn from_image<Image, P>(image: &Image, binarization: Binarization, inverted: bool, connectivity: Connectivity) -> Self
+    where
+        Image: GenericImageView<Pixel = P>,
+        P: Pixel<Subpixel = u8>,
+    {
+        Self::try_from_image(image, binarization, inverted, connectivity).unwrap_or_else(|error| panic!("{error}"))
+    }
+    
+    /// Works like `from_image`, but returns an error instead of panicking.
+    pub fn try_from_image<Image, P>(image: &Image, binarization: Binarization, inverted: bool, connectivity: Connectivity) -> Result<Self, BuildError>
+    where
+        Image: GenericImageView<Pixel = P>,
+        P: Pixel<Subpixel = u8>,
+    {
+        let dimensions = image.dimensions();
+        match binarization {
+            Binarization::Threshold(threshold) => {
+                let rows = threshold_rows(image, threshold);
+                Self::from_binary_rows(dimensions, inverted, connectivity, rows)
+            },
+            Binarization::Otsu => {
+                let rows = threshold_rows(image, otsu_threshold(image));
+                Self::from_binary_rows(dimensions, inverted, connectivity, rows)
+            },
+            Binarization::Niblack { .. } | Binarization::Sauvola { .. } => {
+                let rows = AdaptiveRowIter::new(image, binarization)
+                    .map(|row| row.into_iter());
+                Self::from_binary_rows(dimensions, inverted, connectivity, rows)
+            },
+        }
+    }
+    
+    /// Creates a new instance of `ImageContourCollection`
+    /// containing contours of a 1-bit image packed 8 pixels per byte,
+    /// the most significant bit first (as in PBM or fax TIFF files).
+    /// 
+    /// Each row starts `stride` bytes after the previous one.
+    /// Bit value 1 is considered white. Padding bits at the end of the rows are ignored.
+    /// 
+    /// `inverted` and `connectivity` have the same meaning as in `new`.
+    /// 
+    /// Panics if the `buffer` is too short or `stride` is too small for the given `width`.
+    pub fn from_packed(buffer: &[u8], width: u32, height: u32, stride: usize, inverted: bool, connectivity: Connectivity) -> Self {
+        Self::try_from_packed(buffer, width, height, stride, inverted, connectivity).unwrap_or_else(|error| panic!("{error}"))
+    }
+    
+    /// Works like `from_packed`, but returns an error instead of panicking.
+    pub fn try_from_packed(buffer: &[u8], width: u32, height: u32, stride: usize, inverted: bool, connectivity: Connectivity) -> Result<Self, BuildError> {
+        check_dimensions(width, height)?;
+        let row_length = (width as usize).div_ceil(8);
+        if stride < row_length {
+            return Err(BuildError::StrideTooSmall { stride, row_length });
+        }
+        let expected_length = if height == 0 { 0 } else { stride * (height as usize - 1) + row_length };
+        if buffer.len() < expected_length {
+            return Err(BuildError::BufferTooShort { length: buffer.len(), expected_length });
         }
         
-        builder.into()
+        let mut builder = StreamingBuilder::new(width, inverted, connectivity);
+        for y in 0..height as usize {
+            let start = y * stride;
+            builder.push_packed_row(&buffer[start..start + row_length]);
+        }
+        builder.try_finish()
+    }
+    
+    /// Creates a new instance of `ImageContourCollection`
+    /// from rows given as x coordinates of their changes, skipping pixel scanning.
+    /// 
+    /// Each row lists the boundaries of its foreground runs:
+    /// the start of the first run, the end of the first run, the start of the second one, and so on.
+    /// The changes should be in strictly ascending order, their number should be even,
+    /// and all of them should lie within `0..=width`.
+    /// Otherwise, an error describing the first invalid row is returned.
+    pub fn from_row_changes<Row: AsRef<[i32]>>(
+        width: u32, rows: impl IntoIterator<Item = Row>, connectivity: Connectivity,
+    ) -> Result<Self, BuildError> {
+        let mut builder = StreamingBuilder::try_new(width, false, connectivity)?;
+        for row in rows {
+            builder.push_change_row(row.as_ref())?;
+        }
+        builder.try_finish()
+    }
+    
+    /// Creates a new instance of `ImageContourCollection`
+    /// from rows given as run lengths (e. g. from a CCITT fax decoder), skipping pixel scanning.
+    /// 
+    /// Each row lists lengths of alternating background and foreground runs
+    /// starting with a background run, which can have zero length.
+    /// If the runs of a row end before `width`, the rest of the row is considered background.
+    /// If they end past `width`, an error is returned.
+    pub fn from_row_runs<Row: AsRef<[u32]>>(
+        width: u32, rows: impl IntoIterator<Item = Row>, connectivity: Connectivity,
+    ) -> Result<Self, BuildError> {
+        let mut builder = StreamingBuilder::try_new(width, false, connectivity)?;
+        for row in rows {
+            builder.push_run_row(row.as_ref())?;
+        }
+        builder.try_finish()
+    }
+    
+    /// Creates a new instance of `ImageContourCollection` from the pixels
+    /// inside the `orthopolygons` (more precisely, by the even-odd rule),
+    /// e. g. contours of other collections, skipping pixel scanning.
+    /// 
+    /// The `bounds` become the traced image bounds, the pixels outside them are clipped.
+    pub fn from_orthopolygons<'a, Ortho>(
+        orthopolygons: impl Iterator<Item = &'a Ortho>, bounds: Box2D<i32>, connectivity: Connectivity,
+    ) -> Self where Ortho: Orthopolygonlike + 'a {
+        Self::try_from_orthopolygons(orthopolygons, bounds, connectivity)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+    
+    /// Works like `from_orthopolygons` but returns an error
+    /// if the `bounds` are too large.
+    pub fn try_from_orthopolygons<'a, Ortho>(
+        orthopolygons: impl Iterator<Item = &'a Ortho>, bounds: Box2D<i32>, connectivity: Connectivity,
+    ) -> Result<Self, BuildError> where Ortho: Orthopolygonlike + 'a {
+        RowRuns::of_orthopolygons(orthopolygons, bounds).trace(bounds, connectivity)
+    }
+    
+    /// Creates a new instance of `ImageContourCollection` from the result
+    /// of a boolean `operation` on two sets of pixels given by orthopolygons,
+    /// e. g. contours of other collections.
+    /// 
+    /// In each set, the pixels inside the orthopolygons by the nonzero winding rule are taken.
+    /// So, holes should be anticlockwise, as inner contours are,
+    /// and overlapping clockwise orthopolygons are united.
+    /// 
+    /// The result is exact, its outer contours and holes get a correct hierarchy.
+    /// The bounding box of the result becomes the traced image bounds.
+    /// 
+    /// Panics if the collection cannot be built, see `try_from_boolean_operation`.
+    pub fn from_boolean_operation<'a, 'b, A, B>(
+        operation: BooleanOperation,
+        a: impl Iterator<Item = &'a A>, b: impl Iterator<Item = &'b B>,
+        connectivity: Connectivity,
+    ) -> Self where A: Orthopolygonlike + 'a, B: Orthopolygonlike + 'b {
+        Self::try_from_boolean_operation(operation, a, b, connectivity)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+    
+    /// Works like `from_boolean_operation` but returns an error
+    /// if the bounding box of the result is too large.
+    pub fn try_from_boolean_operation<'a, 'b, A, B>(
+        operation: BooleanOperation,
+        a: impl Iterator<Item = &'a A>, b: impl Iterator<Item = &'b B>,
+        connectivity: Connectivity,
+    ) -> Result<Self, BuildError> where A: Orthopolygonlike + 'a, B: Orthopolygonlike + 'b {
+        let bands = combine_bands(operation, &orthopolygon_bands(a), &orthopolygon_bands(b));
+        trace_bands(&bands, connectivity)
+    }
+    
+    /// Creates a new instance of `ImageContourCollection` from the result
+    /// of a morphological `operation` with a square structuring element
+    /// of (2·`radius` + 1)×(2·`radius` + 1) pixels on a set of pixels given by orthopolygons,
+    /// e. g. contours of other collections.
+    /// 
+    /// The pixels inside the orthopolygons by the nonzero winding rule are taken,
+    /// see `from_boolean_operation`. The pixels outside them are background.
+    /// The work depends on the number of edges, not on the size of the orthopolygons.
+    /// 
+    /// The result is exact, merged or vanished components get a correct hierarchy.
+    /// The bounding box of the result becomes the traced image bounds.
+    /// 
+    /// Panics if the collection cannot be built, see `try_from_morphological_operation`.
+    pub fn from_morphological_operation<'a, Ortho>(
+        operation: MorphologicalOperation,
+        orthopolygons: impl Iterator<Item = &'a Ortho>, radius: u32,
+        connectivity: Connectivity,
+    ) -> Self where Ortho: Orthopolygonlike + 'a {
+        Self::try_from_morphological_operation(operation, orthopolygons, radius, connectivity)
+            .unwrap_or_else(|error| panic!("{error}"))
     }
     
-    /// Gets width and height of the original image.
+    /// Works like `from_morphological_operation` but returns an error
+    /// if the dilated pixels do not fit in `i32` coordinates.
+    pub fn try_from_morphological_operation<'a, Ortho>(
+        operation: MorphologicalOperation,
+        orthopolygons: impl Iterator<Item = &'a Ortho>, radius: u32,
+        connectivity: Connectivity,
+    ) -> Result<Self, BuildError> where Ortho: Orthopolygonlike + 'a {
+        let bands = orthopolygon_bands(orthopolygons);
+        let morphed = morph_bands(operation, &bands, radius).ok_or_else(|| morphology::too_large(&bands, radius))?;
+        trace_bands(&morphed, connectivity)
+    }
+    
+    /// Builds the collection from rows of binary pixel values, top to bottom.
+    fn from_binary_rows<Row>(
+        (width, height): (u32, u32), inverted: bool, connectivity: Connectivity,
+        rows: impl Iterator<Item = Row>,
+    ) -> Result<Self, BuildError> where Row: Iterator<Item = bool> {
+        check_dimensions(width, height)?;
+        let mut builder = StreamingBuilder::new(width, inverted, connectivity);
+        for row in rows {
+            builder.push_binary_pixels(row);
+        }
+        builder.try_finish()
+    }
+    
+    /// Checks the hierarchy tree, the point links and the nesting of the contours:
+    /// each contour should lie inside its parent, and the outermost ones should lie
+    /// within the image bounds.
+    /// 
+    /// A collection built by any of the constructors is always valid,
+    /// so this is mostly useful for collections obtained in other ways.
+    /// Returns the first inconsistency found.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validation::validate(self)
+    }
+    
+    /// Shifts all the contour points, so the upper-left corner
+    /// of the traced image gets coordinates (`left`, `top`).
+    fn moved_to(mut self, left: i32, top: i32) -> Self {
+        for point in &mut self.point_list {
+            point.x += left;
+            point.y += top;
+        }
+        self.left = left;
+        self.top = top;
+        self
+    }
+    
+    /// Gets coordinates of the upper-left corner of the traced image.
+    /// They are zero unless the collection is built by `from_sub_image` or `new_in_rect`.
+    pub fn origin(&self) -> (i32, i32) {
+        (self.left, self.top)
+    }
+    
+    /// Gets width and height of the traced image.
     pub fn dimensions(&self) -> (i32, i32) {
         (self.width, self.height)
     }
     
+    /// Gets the pixel connectivity the contours were traced with.
+    pub fn connectivity(&self) -> Connectivity {
+        self.connectivity
+    }
+    
+    /// Gets the contour with the given `id`, see `Contour::id`,
+    /// or `None` if there is no such contour in the collection.
+    pub fn contour<'a>(&'a self, id: usize) -> Option<Contour<'a>> {
+        Contour::with_id(&self.hierarchy, &self.point_list, id)
+    }
+    
+    /// Gets the total number of contours, outer or inner.
+    pub fn contour_count(&self) -> usize {
+        self.hierarchy.len() - 1
+    }
+    
+    /// Gets the total number of vertices of all the contours.
+    pub fn vertex_count(&self) -> usize {
+        // Only even vertices are stored
+        2 * self.point_list.len()
+    }
+    
     /// Iterates only the top-level contours without any descendants.
     pub fn outermost_contours<'a>(&'a self) -> ChildContourIter<'a> {
         ChildContourIter::new(&self.hierarchy, &self.point_list, 0, false)
@@ -117,3 +486,13 @@ impl ImageContourCollection {
         DescendantContourIter::new(&self.hierarchy, &self.point_list, 0, false)
     }
 }
+
+/// Contour coordinates are `i32`, so the image bounds should fit in it.
+fn check_dimensions(width: impl Into<u64>, height: impl Into<u64>) -> Result<(), BuildError> {
+    let (width, height) = (width.into(), height.into());
+    if width > i32::MAX as u64 || height > i32::MAX as u64 {
+        Err(BuildError::TooLarge { width, height })
+    } else {
+        Ok(())
+    }
+}
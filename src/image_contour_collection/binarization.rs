@@ -0,0 +1,276 @@
+use image::{GenericImageView, Pixel};
+
+/// A method of converting a grayscale or color image into a binary one.
+/// 
+/// Color pixels are converted to luma first.
+/// A pixel is considered white if its luma is greater than the threshold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Binarization {
+    /// A fixed global threshold.
+    Threshold(u8),
+    
+    /// A global threshold selected by the Otsu’s method[^1].
+    /// 
+    /// [^1]: Nobuyuki Otsu, 1979:
+    /// _A threshold selection method from gray-level histograms_.
+    Otsu,
+    
+    /// A local threshold `m + k·s`, where `m` and `s` are the mean
+    /// and the standard deviation of luma in a square window around the pixel[^1].
+    /// 
+    /// `window_size` should be odd. Typical `k` is −0.2.
+    /// 
+    /// [^1]: Wayne Niblack, 1986: _An introduction to digital image processing_.
+    Niblack { window_size: u32, k: f64 },
+    
+    /// A local threshold `m·(1 + k·(s / r − 1))`, where `m` and `s` are the mean
+    /// and the standard deviation of luma in a square window around the pixel[^1].
+    /// 
+    /// `window_size` should be odd. Typical `k` is 0.34, typical `r` is 128.
+    /// 
+    /// [^1]: Jaakko Sauvola, Matti Pietikäinen, 2000:
+    /// _Adaptive document image binarization_.
+    Sauvola { window_size: u32, k: f64, r: f64 },
+}
+
+/// Iterates rows of binary pixel values of the `image`
+/// binarized using a global `threshold`.
+pub fn threshold_rows<'a, Image, P>(image: &'a Image, threshold: u8)
+    -> impl Iterator<Item = impl Iterator<Item = bool> + 'a> + 'a
+where
+    Image: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = u8>,
+{
+    let (width, height) = image.dimensions();
+    (0..height).map(move |y|
+        (0..width).map(move |x| luma(image.get_pixel(x, y)) > threshold)
+    )
+}
+
+/// Selects a global threshold for the `image` using the Otsu’s method.
+pub fn otsu_threshold<Image, P>(image: &Image) -> u8
+where
+    Image: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = u8>,
+{
+    let mut histogram = [0; 256];
+    for (_, _, pixel) in image.pixels() {
+        histogram[luma(pixel) as usize] += 1;
+    }
+    otsu_threshold_from_histogram(&histogram)
+}
+
+/// Iterates rows of binary pixel values of an image
+/// binarized using a local threshold (Niblack’s or Sauvola’s).
+/// 
+/// Only the column sums of the rows within the window are stored,
+/// so the memory usage is proportional to the image width.
+pub struct AdaptiveRowIter<'a, Image> {
+    image: &'a Image,
+    binarization: Binarization,
+    radius: u32,
+    y: u32,
+    /// Sum of luma values in each column within the window rows.
+    column_sums: Vec<u64>,
+    /// Sum of squared luma values in each column within the window rows.
+    column_square_sums: Vec<u64>,
+}
+
+impl<'a, Image, P> AdaptiveRowIter<'a, Image>
+where
+    Image: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = u8>,
+{
+    /// Panics if `binarization` is not a local one.
+    pub fn new(image: &'a Image, binarization: Binarization) -> Self {
+        let window_size = match binarization {
+            Binarization::Niblack { window_size, .. } | Binarization::Sauvola { window_size, .. } => window_size,
+            _ => panic!("{binarization:?} is not a local binarization"),
+        };
+        let radius = window_size / 2;
+        let (width, height) = image.dimensions();
+        
+        let mut iter = Self {
+            image, binarization, radius, y: 0,
+            column_sums: vec![0; width as usize],
+            column_square_sums: vec![0; width as usize],
+        };
+        for y in 0..(radius + 1).min(height) {
+            iter.add_row(y);
+        }
+        iter
+    }
+    
+    fn add_row(&mut self, y: u32) {
+        for x in 0..self.image.width() {
+            let value = luma(self.image.get_pixel(x, y)) as u64;
+            self.column_sums[x as usize] += value;
+            self.column_square_sums[x as usize] += value * value;
+        }
+    }
+    
+    fn remove_row(&mut self, y: u32) {
+        for x in 0..self.image.width() {
+            let value = luma(self.image.get_pixel(x, y)) as u64;
+            self.column_sums[x as usize] -= value;
+            self.column_square_sums[x as usize] -= value * value;
+        }
+    }
+    
+    fn threshold(&self, mean: f64, deviation: f64) -> f64 {
+        match self.binarization {
+            Binarization::Niblack { k, .. } => mean + k * deviation,
+            Binarization::Sauvola { k, r, .. } => mean * (1.0 + k * (deviation / r - 1.0)),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<'a, Image, P> Iterator for AdaptiveRowIter<'a, Image>
+where
+    Image: GenericImageView<Pixel = P>,
+    P: Pixel<Subpixel = u8>,
+{
+    type Item = Vec<bool>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        let (width, height) = self.image.dimensions();
+        let (y, radius) = (self.y, self.radius);
+        if y >= height {
+            return None;
+        }
+        
+        let row_count = ((y + radius).min(height - 1) + 1 - y.saturating_sub(radius)) as u64;
+        let mut sum = 0;
+        let mut square_sum = 0;
+        for x in 0..(radius + 1).min(width) {
+            sum += self.column_sums[x as usize];
+            square_sum += self.column_square_sums[x as usize];
+        }
+        
+        let mut row = Vec::with_capacity(width as usize);
+        for x in 0..width {
+            let column_count = ((x + radius).min(width - 1) + 1 - x.saturating_sub(radius)) as u64;
+            let count = (row_count * column_count) as f64;
+            let mean = sum as f64 / count;
+            let variance = (square_sum as f64 / count - mean * mean).max(0.0);
+            let threshold = self.threshold(mean, variance.sqrt());
+            row.push(luma(self.image.get_pixel(x, y)) as f64 > threshold);
+            
+            // Slide the window to the right
+            if x + radius + 1 < width {
+                sum += self.column_sums[(x + radius + 1) as usize];
+                square_sum += self.column_square_sums[(x + radius + 1) as usize];
+            }
+            if x >= radius {
+                sum -= self.column_sums[(x - radius) as usize];
+                square_sum -= self.column_square_sums[(x - radius) as usize];
+            }
+        }
+        
+        // Slide the window down
+        if y + radius + 1 < height {
+            self.add_row(y + radius + 1);
+        }
+        if y >= radius {
+            self.remove_row(y - radius);
+        }
+        self.y += 1;
+        Some(row)
+    }
+}
+
+fn luma<P: Pixel<Subpixel = u8>>(pixel: P) -> u8 {
+    pixel.to_luma()[0]
+}
+
+/// Maximizes the between-class variance.
+/// If the histogram has only one non-empty bin, returns `FALLBACK_THRESHOLD`.
+fn otsu_threshold_from_histogram(histogram: &[u64; 256]) -> u8 {
+    let total_count: u64 = histogram.iter().sum();
+    let total_sum: u64 = histogram.iter().enumerate().map(|(value, &count)| value as u64 * count).sum();
+    
+    let mut best_threshold = FALLBACK_THRESHOLD;
+    let mut best_variance = 0.0;
+    let mut background_count = 0;
+    let mut background_sum = 0;
+    
+    // The last value is skipped, since the foreground would be empty
+    for (threshold, &count) in histogram.iter().enumerate().take(255) {
+        background_count += count;
+        background_sum += threshold as u64 * count;
+        let foreground_count = total_count - background_count;
+        if background_count == 0 || foreground_count == 0 {
+            continue;
+        }
+        
+        let background_mean = background_sum as f64 / background_count as f64;
+        let foreground_mean = (total_sum - background_sum) as f64 / foreground_count as f64;
+        let difference = foreground_mean - background_mean;
+        let variance = background_count as f64 * foreground_count as f64 * difference * difference;
+        if variance > best_variance {
+            best_variance = variance;
+            best_threshold = threshold as u8;
+        }
+    }
+    best_threshold
+}
+
+const FALLBACK_THRESHOLD: u8 = 127;
+
+
+// ---------
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+    use image::{GrayImage, Rgb, RgbImage};
+    use super::*;
+    
+    #[test_case(&[(0, 10), (255, 10)] => 0)]
+    #[test_case(&[(10, 5), (11, 5), (200, 3), (210, 7)] => 11)]
+    #[test_case(&[(50, 1), (60, 1), (70, 1), (240, 1)] => 70)]
+    #[test_case(&[(42, 100)] => FALLBACK_THRESHOLD)]
+    #[test_case(&[] => FALLBACK_THRESHOLD)]
+    fn test_otsu_threshold(bins: &[(usize, u64)]) -> u8 {
+        let mut histogram = [0; 256];
+        for &(value, count) in bins {
+            histogram[value] = count;
+        }
+        otsu_threshold_from_histogram(&histogram)
+    }
+    
+    #[test]
+    fn test_threshold_rows_of_color_image() {
+        let image = RgbImage::from_fn(3, 2, |x, y| if (x + y) % 2 == 0 { Rgb([250, 240, 230]) } else { Rgb([20, 10, 0]) });
+        let rows: Vec<Vec<_>> = threshold_rows(&image, 127).map(|row| row.collect()).collect();
+        assert_eq!(rows, vec![vec![true, false, true], vec![false, true, false]]);
+    }
+    
+    #[test]
+    fn test_sauvola_on_uneven_background() {
+        // Dark text on a background getting darker from left to right.
+        // No global threshold can separate them
+        let image = GrayImage::from_fn(40, 9, |x, y| {
+            let background = 250 - 5 * x as u8;
+            if y == 4 && x % 4 == 0 { image::Luma([background / 3]) } else { image::Luma([background]) }
+        });
+        let binarization = Binarization::Sauvola { window_size: 7, k: 0.34, r: 128.0 };
+        let rows: Vec<_> = AdaptiveRowIter::new(&image, binarization).collect();
+        
+        assert_eq!(rows.len(), 9);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, &value) in row.iter().enumerate() {
+                let is_text = y == 4 && x % 4 == 0;
+                assert_eq!(value, !is_text, "pixel ({x}, {y})");
+            }
+        }
+    }
+    
+    #[test]
+    fn test_adaptive_rows_of_empty_image() {
+        let image = GrayImage::new(0, 0);
+        let binarization = Binarization::Niblack { window_size: 15, k: -0.2 };
+        assert_eq!(AdaptiveRowIter::new(&image, binarization).count(), 0);
+    }
+}
@@ -1,5 +1,5 @@
 use std::collections::VecDeque;
-use super::ImageContourCollection;
+use super::{ImageContourCollection, Connectivity, BuildError};
 use super::row_pair_changes::RowPairChange;
 use super::feature_automaton::{FeatureKind, Feature, FeatureAutomaton};
 use super::hierarchy_builder::HierarchyBuilder;
@@ -8,9 +8,7 @@ use super::point_list_builder::PointListBuilder;
 /// Builds the point list and contour hierarchy
 /// from changes in image rows pairs.
 pub struct ContourCollectionBuilder {
-    // Original image dimensions
-    width: i32,
-    height: i32,
+    connectivity: Connectivity,
     
     // Helper builders
     point_list: PointListBuilder,
@@ -25,23 +23,59 @@ pub struct ContourCollectionBuilder {
 }
 
 impl ContourCollectionBuilder {
-    pub fn new(width: i32, height: i32) -> Self {
+    pub fn new(connectivity: Connectivity) -> Self {
         Self {
-            width, height,
-            feature_automaton: FeatureAutomaton::new(),
+            connectivity,
+            feature_automaton: FeatureAutomaton::new(connectivity),
             point_list: PointListBuilder::new(),
             hierarchy: HierarchyBuilder::new(),
             queue: VecDeque::new(),
         }
     }
     
-    pub fn into(self) -> ImageContourCollection {
-        assert!(self.queue.is_empty(), "Queue left non-empty");
-        ImageContourCollection {
-            width: self.width, height: self.height,
-            hierarchy: self.hierarchy.into(),
-            point_list: self.point_list.into()
+    /// Creates a builder for a horizontal strip of the image below a seam
+    /// crossed by `seam_end_count` open contour ends.
+    /// 
+    /// The open ends are represented by placeholder points and contours,
+    /// which are resolved when the strip is appended
+    /// to the builder of the part of the image above the seam (see `append_strip`).
+    pub fn new_strip(connectivity: Connectivity, seam_end_count: usize) -> Self {
+        Self {
+            connectivity,
+            feature_automaton: FeatureAutomaton::new(connectivity),
+            point_list: PointListBuilder::with_placeholders(seam_end_count),
+            hierarchy: HierarchyBuilder::with_placeholders(seam_end_count),
+            queue: (0..seam_end_count).map(|index| (index, index + 1)).collect(),
+        }
+    }
+    
+    /// Stitches a strip built by a builder created with `new_strip`
+    /// to the bottom of the part of the image built by this builder.
+    /// 
+    /// The open ends left in the queue are connected to the strip’s placeholders,
+    /// and the strip’s open ends take their place in the queue.
+    pub fn append_strip(&mut self, strip: Self) {
+        let (seam_points, seam_contours): (Vec<_>, Vec<_>) = self.queue.drain(..).unzip();
+        let map_point = self.point_list.append_strip(strip.point_list, seam_points);
+        let map_contour = self.hierarchy.append_strip(strip.hierarchy, seam_contours, &map_point);
+        self.queue.extend(strip.queue.into_iter()
+            .map(|(point, contour)| (map_point(point), map_contour(contour))));
+    }
+    
+    /// Returns the constructed collection.
+    /// `width` and `height` are the original image dimensions.
+    /// 
+    /// Returns an error if some contours are left open.
+    pub fn into(self, width: i32, height: i32) -> Result<ImageContourCollection, BuildError> {
+        if !self.queue.is_empty() {
+            return Err(BuildError::OpenContourEnds { count: self.queue.len() });
         }
+        Ok(ImageContourCollection {
+            left: 0, top: 0, width, height,
+            connectivity: self.connectivity,
+            hierarchy: self.hierarchy.into()?,
+            point_list: self.point_list.into()
+        })
     }
     
     pub fn add_row_pair_change(&mut self, y: i32, change: RowPairChange) {
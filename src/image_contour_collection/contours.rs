@@ -1,7 +1,8 @@
+use std::iter;
 use std::num::NonZeroUsize;
-use euclid::default::Point2D;
+use euclid::default::{Point2D, Vector2D};
 use crate::more_itertools::MoreIterTools;
-use crate::geometry::{Orthopolygonlike, Polygonlike};
+use crate::geometry::{Orthopolygonlike, Polygon, Polygonlike, Moments, orthopolygon_convex_hull};
 use super::hierarchy_builder::HierarchyItem;
 use super::point_list_builder::PointListItem;
 
@@ -26,6 +27,25 @@ impl<'a> Contour<'a> {
         self.is_outer
     }
     
+    /// Gets the contour’s id, which is its index in the hierarchy.
+    /// The id can be stored and resolved later with `ImageContourCollection::contour`.
+    /// 
+    /// Ids are only valid within the contour’s collection.
+    /// A collection created e. g. by `filtered` has its own ids.
+    pub fn id(&self) -> usize {
+        self.index.get()
+    }
+    
+    /// Finds the contour with the given `id` in the `hierarchy`,
+    /// or returns `None` if there is no such contour.
+    pub(super) fn with_id(hierarchy: &'a[HierarchyItem], point_list: &'a[PointListItem], id: usize) -> Option<Self> {
+        let index = NonZeroUsize::new(id).filter(|_| id < hierarchy.len())?;
+        // Outermost contours are outer, and their children are inner, and so on
+        let depth = iter::successors(Some(id), |&index| Some(hierarchy[index].parent).filter(|&parent| parent != 0)).count();
+        let is_outer = depth % 2 == 1;
+        Some(Self { hierarchy, point_list, is_outer, index })
+    }
+    
     /// Iterates the contour’s child contours.
     /// 
     /// All children of an outer contour are inner contours and vice versa.
@@ -48,6 +68,48 @@ impl<'a> Contour<'a> {
             Self { hierarchy: self.hierarchy, point_list: self.point_list, is_outer: is_outer, index }
         })
     }
+    
+    /// Iterates the contour’s ancestors, i.e. parent, grandparent, etc,
+    /// up to the outermost contour.
+    pub fn ancestors(&self) -> impl Iterator<Item = Self> + use<'a> {
+        iter::successors(self.parent(), |contour| contour.parent())
+    }
+    
+    /// Number of the contour’s ancestors.
+    /// Zero for outermost contours, one for their holes, and so on.
+    pub fn depth(&self) -> usize {
+        self.ancestors().count()
+    }
+    
+    /// Iterates the other children of the contour’s parent
+    /// or the other outermost contours for outermost contours.
+    pub fn siblings(&self) -> impl Iterator<Item = Self> + use<'a> {
+        let parent_index = self.hierarchy[self.index.get()].parent;
+        let index = self.index;
+        ChildContourIter::new(self.hierarchy, self.point_list, parent_index, !self.is_outer)
+            .filter(move |contour| contour.index != index)
+    }
+    
+    /// Area of the contour minus the areas of its children.
+    /// For an outer contour, this is the number of pixels
+    /// of the connected component, excluding its holes.
+    pub fn net_area(&self) -> i64 {
+        self.area() - self.children().map(|child| child.area()).sum::<i64>()
+    }
+    
+    /// Raw moments of the pixels enclosed by the contour, excluding the ones enclosed by its children,
+    /// with pixel coordinates taken relative to the `origin`.
+    pub fn net_moments_about(&self, origin: Point2D<i32>) -> Moments {
+        self.children().fold(self.moments_about(origin), |moments, child| moments - child.moments_about(origin))
+    }
+    
+    /// Centroid of the area enclosed by the contour, excluding the area enclosed by its children,
+    /// or `None` if the area is zero.
+    pub fn net_centroid(&self) -> Option<Point2D<f64>> {
+        let origin = self.bounding_box().min;
+        self.net_moments_about(origin).mean()
+            .map(|mean| origin.to_f64() + mean + Vector2D::new(0.5, 0.5))
+    }
 }
 
 impl<'a> Orthopolygonlike for Contour<'a> {
@@ -63,6 +125,10 @@ impl<'a> Polygonlike<i32> for Contour<'a> {
             .circular_pairs()
             .flat_map(|(p0, p1)| [p0, Point2D::new(p1.x, p0.y)])
     }
+    
+    fn convex_hull(&self) -> Polygon<i32> {
+        orthopolygon_convex_hull(self.even_vertices())
+    }
 }
 
 
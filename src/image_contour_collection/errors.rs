@@ -0,0 +1,135 @@
+use std::{fmt, io};
+use image::math::Rect;
+use super::InvalidRowError;
+
+/// Describes why an `ImageContourCollection` could not be built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildError {
+    /// The image width or height does not fit in `i32` contour coordinates.
+    TooLarge { width: u64, height: u64 },
+    /// The region `rect` does not fit in the image.
+    RectOutOfBounds { rect: Rect },
+    /// The packed row `stride` is smaller than the length of a row.
+    StrideTooSmall { stride: usize, row_length: usize },
+    /// The packed buffer is shorter than `expected_length` bytes.
+    BufferTooShort { length: usize, expected_length: usize },
+    /// A row given as changes or runs is invalid.
+    InvalidRow(InvalidRowError),
+    /// Some open contour ends were left after the last row.
+    OpenContourEnds { count: usize },
+    /// Some contours were left unclosed in the hierarchy.
+    UnclosedContours,
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooLarge { width, height } => write!(f, "Image {width}×{height} is too large"),
+            Self::RectOutOfBounds { rect } => write!(f, "Region {rect:?} is out of the image bounds"),
+            Self::StrideTooSmall { stride, row_length } => write!(f, "Stride {stride} is smaller than the row length {row_length}"),
+            Self::BufferTooShort { length, expected_length } => write!(f, "Buffer length {length} is less than {expected_length}"),
+            Self::InvalidRow(error) => write!(f, "Invalid row: {error}"),
+            Self::OpenContourEnds { count } => write!(f, "{count} open contour ends left"),
+            Self::UnclosedContours => write!(f, "Some contours left unclosed"),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidRow(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<InvalidRowError> for BuildError {
+    fn from(error: InvalidRowError) -> Self {
+        Self::InvalidRow(error)
+    }
+}
+
+/// Describes an inconsistency found by `ImageContourCollection::validate`.
+/// `contour` is the contour id, see `Contour::id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The hierarchy has no root item.
+    MissingRoot,
+    /// A child or sibling link of the contour points outside of the hierarchy.
+    LinkOutOfRange { contour: usize },
+    /// The contour is reached twice while walking the hierarchy tree.
+    Cycle { contour: usize },
+    /// The parent of the contour differs from the item it is linked from.
+    IncorrectParent { contour: usize },
+    /// The contour cannot be reached from the root.
+    Unreachable { contour: usize },
+    /// A point link of the contour points outside of the point list.
+    PointOutOfRange { contour: usize },
+    /// Following the point links of the contour does not lead back to its head.
+    OpenContour { contour: usize },
+    /// The contour has less than two even vertices.
+    Degenerate { contour: usize },
+    /// A point of the contour is not inside its parent contour.
+    OutsideParent { contour: usize },
+    /// A point of the outermost contour is outside the image bounds.
+    OutsideImage { contour: usize },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingRoot => write!(f, "Hierarchy has no root"),
+            Self::LinkOutOfRange { contour } => write!(f, "Contour {contour}: hierarchy link is out of range"),
+            Self::Cycle { contour } => write!(f, "Contour {contour}: cycle in hierarchy tree"),
+            Self::IncorrectParent { contour } => write!(f, "Contour {contour}: incorrect parent"),
+            Self::Unreachable { contour } => write!(f, "Contour {contour}: not accessible from the root"),
+            Self::PointOutOfRange { contour } => write!(f, "Contour {contour}: point link is out of range"),
+            Self::OpenContour { contour } => write!(f, "Contour {contour}: not closed"),
+            Self::Degenerate { contour } => write!(f, "Contour {contour}: less than two even vertices"),
+            Self::OutsideParent { contour } => write!(f, "Contour {contour}: a point is outside its parent contour"),
+            Self::OutsideImage { contour } => write!(f, "Contour {contour}: a point is outside the image bounds"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError { }
+
+/// Describes why an `ImageContourCollection` could not be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    /// Reading failed.
+    Io(io::Error),
+    /// The data does not start with the expected magic bytes.
+    UnknownFormat,
+    /// The format version is not supported.
+    UnsupportedVersion { version: u8 },
+    /// The data ends unexpectedly.
+    Truncated,
+    /// There is more data after the last contour.
+    TrailingData,
+    /// The data is inconsistent.
+    Corrupt { reason: &'static str },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "Read error: {error}"),
+            Self::UnknownFormat => write!(f, "Not a contour collection"),
+            Self::UnsupportedVersion { version } => write!(f, "Unsupported format version {version}"),
+            Self::Truncated => write!(f, "Data is truncated"),
+            Self::TrailingData => write!(f, "Unexpected data after the end"),
+            Self::Corrupt { reason } => write!(f, "Data is corrupt: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
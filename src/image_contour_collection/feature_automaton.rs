@@ -1,3 +1,4 @@
+use super::Connectivity;
 use super::row_pair_changes::{RowPairChangeKind, RowPairChange};
 
 /// Contour feature kinds:
@@ -53,18 +54,26 @@ pub struct FeatureAutomaton {
     
     /// Stores x coordinate of the representative point of the feature.
     feature_x: i32,
+    
+    /// Transitions for `Both` changes.
+    /// Only they depend on the connectivity.
+    step_if_both: &'static [(usize, bool, FeatureKind); 6],
 }
 
 impl FeatureAutomaton {
-    pub fn new() -> Self {
-        Self { state: 0, feature_x: 0 }
+    pub fn new(connectivity: Connectivity) -> Self {
+        let step_if_both = match connectivity {
+            Connectivity::Eight => &STEP_IF_BOTH,
+            Connectivity::Four => &STEP_IF_BOTH_4_CONNECTED,
+        };
+        Self { state: 0, feature_x: 0, step_if_both }
     }
     
     pub fn step(&mut self, change: RowPairChange) -> Feature {
         let (new_state, update_x, feature_kind) = match change.kind {
             RowPairChangeKind::Top => STEP_IF_TOP[self.state],
             RowPairChangeKind::Bottom => STEP_IF_BOTTOM[self.state],
-            RowPairChangeKind::Both => STEP_IF_BOTH[self.state],
+            RowPairChangeKind::Both => self.step_if_both[self.state],
         };
         self.state = new_state;
         if update_x {
@@ -117,3 +126,18 @@ const STEP_IF_BOTH: [(usize, bool, FeatureKind); 6] = [
     (5, false, FeatureKind::InnerFoot),
     (4, true,  FeatureKind::Head),
 ];
+
+// With 4-connected foreground, diagonally adjacent foreground pixels
+// are not connected. So a `Both` change in states 1, 2, 4, and 5
+// (where the pixels meet diagonally) is processed as two consecutive changes
+// at the same x: first, the one that closes the foreground run,
+// then, the one that opens a new run.
+// Each entry below is the composition of these two transitions.
+const STEP_IF_BOTH_4_CONNECTED: [(usize, bool, FeatureKind); 6] = [
+    (3, false, FeatureKind::Vertical),   // same as 8-connected
+    (2, true,  FeatureKind::OuterFoot),  // ↑ then ↓
+    (1, false, FeatureKind::Head),       // ↓ then ↑
+    (0, false, FeatureKind::Vertical),   // same as 8-connected
+    (1, false, FeatureKind::RightShelf), // ↓ then ↑
+    (2, true,  FeatureKind::RightShelf), // ↑ then ↓
+];
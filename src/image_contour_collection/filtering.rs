@@ -0,0 +1,78 @@
+use std::num::NonZeroUsize;
+use crate::geometry::Orthopolygonlike;
+use super::{ImageContourCollection, Contour};
+use super::hierarchy_builder::HierarchyItem;
+use super::point_list_builder::PointListItem;
+
+impl ImageContourCollection {
+    /// Creates a new collection without the contours for which `keep` returns `false`.
+    /// The descendants of a removed contour are removed too, since they lie inside it.
+    /// 
+    /// Removing an outer contour erases its connected component along with everything inside it,
+    /// removing a hole fills it solid, including the islands inside it.
+    /// So the whole subtree of a removed contour disappears, whereas erasing the component
+    /// or filling the hole in the image and tracing it again would keep the islands inside.
+    /// 
+    /// `keep` is called for each contour whose parent is kept, parents first.
+    pub fn filtered(&self, mut keep: impl FnMut(&Contour) -> bool) -> Self {
+        let mut is_kept = vec![false; self.hierarchy.len()];
+        is_kept[0] = true;
+        
+        // The contours are iterated depth-first, so the parents are checked before their children
+        for contour in self.all_contours() {
+            let index = contour.id();
+            is_kept[index] = is_kept[self.hierarchy[index].parent] && keep(&contour);
+        }
+        
+        let mut new_indices = vec![0; self.hierarchy.len()];
+        let mut hierarchy = Vec::new();
+        let mut point_list = Vec::new();
+        
+        for (index, item) in self.hierarchy.iter().enumerate() {
+            if is_kept[index] {
+                new_indices[index] = hierarchy.len();
+                let head_point_index = if index == 0 { 0 } else { self.copy_points(item.head_point_index, &mut point_list) };
+                let parent = new_indices[item.parent];
+                hierarchy.push(HierarchyItem { head_point_index, parent, ..Default::default() });
+            }
+        }
+        
+        // Siblings are linked in the order of their indices
+        for index in (1..hierarchy.len()).rev() {
+            let parent = hierarchy[index].parent;
+            hierarchy[index].next_sibling = hierarchy[parent].first_child;
+            hierarchy[parent].first_child = NonZeroUsize::new(index);
+        }
+        
+        Self { hierarchy, point_list, ..*self }
+    }
+    
+    /// Removes outer contours enclosing less than `min_outer_area` pixels
+    /// (e. g. noise specks) and fills holes enclosing less than `min_hole_area` pixels
+    /// (e. g. pinholes). The area includes the area of the contour’s children.
+    /// 
+    /// See `filtered`.
+    pub fn despeckled(&self, min_outer_area: i64, min_hole_area: i64) -> Self {
+        self.filtered(|contour| {
+            let min_area = if contour.is_outer() { min_outer_area } else { min_hole_area };
+            contour.area() >= min_area
+        })
+    }
+    
+    /// Appends the points of the contour starting at `head` to the `point_list`.
+    /// Returns the index of the copied head.
+    fn copy_points(&self, head: usize, point_list: &mut Vec<PointListItem>) -> usize {
+        let start = point_list.len();
+        let mut current = head;
+        loop {
+            let PointListItem { x, y, next } = self.point_list[current];
+            point_list.push(PointListItem { x, y, next: point_list.len() + 1 });
+            current = next;
+            if current == head {
+                break;
+            }
+        }
+        point_list.last_mut().unwrap().next = start;
+        start
+    }
+}
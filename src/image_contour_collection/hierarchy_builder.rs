@@ -1,4 +1,5 @@
 use std::num::NonZeroUsize;
+use super::BuildError;
 
 #[derive(Debug, PartialEq, Eq, Default)]
 pub struct HierarchyItem {
@@ -8,30 +9,55 @@ pub struct HierarchyItem {
     pub first_child: Option<NonZeroUsize>,
 }
 
-#[derive(Debug, PartialEq, Eq, Default)]
+/// A contour head is added at the topmost point of each new contour.
+/// 
+/// When two contours turn out to be parts of the same one, their heads are merged.
+/// Heads of the same contour form a set in a disjoint-set forest
+/// with path compression and union by rank.
+/// The head with the smallest index in a set is the _main head_ of the contour.
+#[derive(Debug, PartialEq, Eq)]
 struct Head {
     point_index: usize,
-    is_alias: bool,
+    /// The contour to the left of the head when it was added.
     parent: usize,
-    hierarchy_index: usize,
+    /// Next head on the path to the root of the set. The root links to itself.
+    link: usize,
+    /// Index of the main head of the set. Valid only in the root.
+    main: usize,
+}
+
+impl Head {
+    fn new(index: usize, point_index: usize, parent: usize) -> Self {
+        Self { point_index, parent, link: index, main: index }
+    }
 }
 
 #[derive(Debug)]
 pub struct HierarchyBuilder {
     heads: Vec<Head>,
+    /// Upper bound of the height of each root head’s tree.
+    /// Kept apart from the heads, so that they stay small.
+    ranks: Vec<u8>,
     contour_to_the_left: usize,
 }
 
 impl HierarchyBuilder {
     pub fn new() -> Self {
-        let root = Default::default();
-        Self { heads: vec![root], contour_to_the_left: 0 }
+        Self::with_placeholders(0)
+    }
+    
+    /// Creates a builder with `count` placeholder contours (indices 1 to `count`)
+    /// that stand for contours crossing a seam from the part of the image above.
+    pub fn with_placeholders(count: usize) -> Self {
+        let heads = (0..=count).map(|index| Head::new(index, 0, 0)).collect();
+        Self { heads, ranks: vec![0; count + 1], contour_to_the_left: 0 }
     }
     
     pub fn add_contour(&mut self, point_index: usize) -> (usize, usize) {
         let parent_contour = self.contour_to_the_left;
         let new_contour = self.heads.len();
-        self.heads.push(Head { point_index, parent: parent_contour, ..Default::default() });
+        self.heads.push(Head::new(new_contour, point_index, parent_contour));
+        self.ranks.push(0);
         (new_contour, parent_contour)
     }
     
@@ -40,52 +66,112 @@ impl HierarchyBuilder {
     }
     
     pub fn merge_contours(&mut self, contour_to_the_right: usize) {
-        let left = self.unalias(self.contour_to_the_left);
-        let right = self.unalias(contour_to_the_right);
-        
-        if left != right {
-            let (from, to) = if left < right { (right, left) } else { (left, right) };
-            let alias = &mut self.heads[from];
-            alias.is_alias = true;
-            alias.parent = to;
+        self.merge(self.contour_to_the_left, contour_to_the_right);
+    }
+    
+    /// Appends the contours of a `strip` builder created by `with_placeholders`.
+    /// Its placeholders stand for the `seam_contours` of this builder.
+    /// Point indices are mapped using `map_point`.
+    /// 
+    /// The merges made in the strip are repeated, so the contours
+    /// continuing across the seam are merged the same way as in a single builder.
+    /// 
+    /// Returns a function mapping contour indices of the strip to indices in this builder.
+    pub fn append_strip<MapPoint>(&mut self, mut strip: Self, seam_contours: Vec<usize>, map_point: MapPoint)
+        -> impl Fn(usize) -> usize + use<MapPoint> where MapPoint: Fn(usize) -> usize {
+        let count = seam_contours.len();
+        let base = self.heads.len();
+        let map = move |index: usize| match index {
+            0 => 0,
+            index if index <= count => seam_contours[index - 1],
+            index => index - count - 1 + base,
+        };
+        
+        for head in &strip.heads[count + 1..] {
+            let index = self.heads.len();
+            self.heads.push(Head::new(index, map_point(head.point_index), map(head.parent)));
+            self.ranks.push(0);
+        }
+        // The outer contour 0 can also be a non-root head of its set
+        for index in 0..strip.heads.len() {
+            let root = strip.find_root(index);
+            if root != index {
+                self.merge(map(index), map(root));
+            }
         }
+        self.contour_to_the_left = map(strip.contour_to_the_left);
+        map
     }
     
-    pub fn into(mut self) -> Vec<HierarchyItem> {
-        assert_eq!(self.unalias(self.contour_to_the_left), 0, "Some contours left unclosed");
+    /// Merges two contours. The lower tree is attached to the root of the higher one.
+    /// The main head of the merged contour is the one with the smaller index.
+    fn merge(&mut self, contour: usize, other_contour: usize) {
+        let root = self.find_root(contour);
+        let other_root = self.find_root(other_contour);
         
+        if root != other_root {
+            let (from, to) = if self.ranks[root] < self.ranks[other_root] {
+                (root, other_root)
+            } else {
+                (other_root, root)
+            };
+            if self.ranks[from] == self.ranks[to] {
+                self.ranks[to] += 1;
+            }
+            let main = self.heads[from].main;
+            self.heads[from].link = to;
+            let to = &mut self.heads[to];
+            to.main = to.main.min(main);
+        }
+    }
+    
+    /// Returns an error if some contours are left unclosed.
+    pub fn into(mut self) -> Result<Vec<HierarchyItem>, BuildError> {
+        if self.unalias(self.contour_to_the_left) != 0 {
+            return Err(BuildError::UnclosedContours);
+        }
+        
+        // Hierarchy indices of the main heads
+        let mut hierarchy_indices = vec![0; self.heads.len()];
         let mut hierarchy = Vec::new();
-        for head in self.heads.iter_mut().filter(|h| !h.is_alias) {
-            head.hierarchy_index = hierarchy.len();
-            hierarchy.push(HierarchyItem { head_point_index: head.point_index, ..Default::default() });
+        for (index, hierarchy_index) in hierarchy_indices.iter_mut().enumerate() {
+            if self.unalias(index) == index {
+                *hierarchy_index = hierarchy.len();
+                hierarchy.push(HierarchyItem { head_point_index: self.heads[index].point_index, ..Default::default() });
+            }
         }
         
         for index in (1..self.heads.len()).rev() {
-            let head = &self.heads[index];
-            if !head.is_alias {
-                let current = head.hierarchy_index;
-                let parent = self.heads[self.unalias(head.parent)].hierarchy_index;
+            if self.unalias(index) == index {
+                let current = hierarchy_indices[index];
+                let parent = hierarchy_indices[self.unalias(self.heads[index].parent)];
                 
                 hierarchy[current].parent = parent;
                 hierarchy[current].next_sibling = hierarchy[parent].first_child;
                 hierarchy[parent].first_child = NonZeroUsize::new(current);
             }
         }
-        hierarchy
+        Ok(hierarchy)
     }
     
-    /// Returns the index of the main head of a contour by an aliased head index.
-    /// TODO: Consider using disjoint-set forest balancing.
-    fn unalias(&self, alias: usize) -> usize {
-        let mut index = alias;
-        while let Head { point_index: _, is_alias: true, parent, hierarchy_index: _ } = self.heads[index] {
-            index = parent;
+    /// Returns the index of the main head of a contour by any of its head indices.
+    fn unalias(&mut self, alias: usize) -> usize {
+        let root = self.find_root(alias);
+        self.heads[root].main
+    }
+    
+    /// Returns the index of the root of the head’s set.
+    /// Halves the path in the same pass, so each head on it links to its grandparent.
+    fn find_root(&mut self, mut index: usize) -> usize {
+        while self.heads[index].link != index {
+            let grandparent = self.heads[self.heads[index].link].link;
+            self.heads[index].link = grandparent;
+            index = grandparent;
         }
         index
     }
 }
 
-
 // ---------
 
 #[cfg(test)]
@@ -94,13 +180,22 @@ mod tests {
 
     #[test]
     fn empty() {
-        let builder = HierarchyBuilder::new();
+        let mut builder = HierarchyBuilder::new();
         
-        let expected_heads = vec![Default::default()];
-        assert_eq!(builder.heads, expected_heads);
+        let expected_heads = vec![head(0, 0, 0)];
+        assert_eq!(summarize_heads(&mut builder), expected_heads);
         
         let expected_hierarchy = vec![root(None)];
-        assert_eq!(builder.into(), expected_hierarchy);
+        assert_eq!(builder.into(), Ok(expected_hierarchy));
+    }
+    
+    #[test]
+    fn unclosed_contour() {
+        let mut builder = HierarchyBuilder::new();
+        let (contour, _) = builder.add_contour(0);
+        builder.cross_contour(contour);
+        
+        assert_eq!(builder.into(), Err(BuildError::UnclosedContours));
     }
     
     /// ```
@@ -121,15 +216,15 @@ mod tests {
     #[test]
     fn test_contour_depicted_above() {
         let expected_heads = vec![
-            /* 0 */ Default::default(),
-            /* 1 */ head(0,  false, 0),
-            /* 2 */ head(1,  true,  1),
-            /* 3 */ head(2,  true,  1),
-            /* 4 */ head(4,  false, 1),
-            /* 5 */ head(5,  false, 3),
-            /* 6 */ head(6,  true,  4),
-            /* 7 */ head(12, false, 1),
-            /* 8 */ head(13, true,  0),
+            /* 0 */ head(0,  0, 0),
+            /* 1 */ head(0,  0, 1),
+            /* 2 */ head(1,  0, 1),
+            /* 3 */ head(2,  0, 1),
+            /* 4 */ head(4,  1, 4),
+            /* 5 */ head(5,  3, 5),
+            /* 6 */ head(6,  1, 4),
+            /* 7 */ head(12, 1, 7),
+            /* 8 */ head(13, 1, 0),
         ];
         let expected_hierarchy = vec![
             /* 0 */ root(NonZeroUsize::new(1)),
@@ -182,12 +277,55 @@ mod tests {
         builder.merge_contours(8);
         builder.merge_contours(0);
         
-        assert_eq!(builder.heads, expected_heads);
-        assert_eq!(builder.into(), expected_hierarchy);
+        assert_eq!(summarize_heads(&mut builder), expected_heads);
+        assert_eq!(builder.into(), Ok(expected_hierarchy));
+    }
+    
+    #[test]
+    fn long_merge_chain() {
+        let count = 1000;
+        let mut builder = HierarchyBuilder::new();
+        for index in 1..=count {
+            assert_eq!(builder.add_contour(index), (index, 0));
+        }
+        // Without balancing, this merge order would make a chain of all the heads
+        for index in (1..count).rev() {
+            builder.cross_contour(index + 1);
+            builder.merge_contours(index);
+        }
+        builder.cross_contour(0);
+        
+        let max_depth = (1..=count).map(|index| set_depth(&builder, index)).max().unwrap();
+        assert!(max_depth <= 10, "Disjoint-set tree is too deep: {max_depth}");
+        assert!((1..=count).all(|index| builder.unalias(index) == 1));
+        
+        let expected_hierarchy = vec![root(NonZeroUsize::new(1)), hier(1, 0, None, None)];
+        assert_eq!(builder.into(), Ok(expected_hierarchy));
+    }
+    
+    /// Point index, parent, and main head of each head.
+    fn summarize_heads(builder: &mut HierarchyBuilder) -> Vec<(usize, usize, usize)> {
+        (0..builder.heads.len())
+            .map(|index| {
+                let main = builder.unalias(index);
+                let Head { point_index, parent, .. } = builder.heads[index];
+                (point_index, parent, main)
+            })
+            .collect()
+    }
+    
+    /// Number of links from the head to the root of its set.
+    fn set_depth(builder: &HierarchyBuilder, mut index: usize) -> usize {
+        let mut depth = 0;
+        while builder.heads[index].link != index {
+            index = builder.heads[index].link;
+            depth += 1;
+        }
+        depth
     }
     
-    const fn head(point_index: usize, is_alias: bool, parent: usize) -> Head {
-        Head { point_index, is_alias, parent, hierarchy_index: 0 }
+    const fn head(point_index: usize, parent: usize, main: usize) -> (usize, usize, usize) {
+        (point_index, parent, main)
     }
     
     const fn root(first_child: Option<NonZeroUsize>) -> HierarchyItem {
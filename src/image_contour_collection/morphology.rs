@@ -0,0 +1,99 @@
+use euclid::default::{Box2D, Point2D};
+use crate::geometry::{Band, MorphologicalOperation, orthopolygon_bands, morph_bands};
+use super::{ImageContourCollection, BuildError};
+use super::row_runs::trace_bands;
+
+impl ImageContourCollection {
+    /// Dilates the foreground with a square structuring element
+    /// of (2·`radius` + 1)×(2·`radius` + 1) pixels.
+    /// 
+    /// The traced image bounds are extended by `radius` on each side,
+    /// so that nothing is clipped. Merged components get a correct hierarchy.
+    /// 
+    /// Panics if the extended bounds do not fit in `i32`, see `try_dilated`.
+    pub fn dilated(&self, radius: u32) -> Self {
+        self.try_dilated(radius).unwrap_or_else(|error| panic!("{error}"))
+    }
+    
+    /// Works like `dilated` but returns an error
+    /// if the extended bounds do not fit in `i32`.
+    pub fn try_dilated(&self, radius: u32) -> Result<Self, BuildError> {
+        let (bounds, r) = (self.bounds(), radius as i64);
+        let moved = |value: i32, delta: i64| i32::try_from(value as i64 + delta);
+        match (moved(bounds.min.x, -r), moved(bounds.min.y, -r), moved(bounds.max.x, r), moved(bounds.max.y, r)) {
+            (Ok(left), Ok(top), Ok(right), Ok(bottom)) => {
+                let bounds = Box2D::new(Point2D::new(left, top), Point2D::new(right, bottom));
+                self.morphed(MorphologicalOperation::Dilation, radius, bounds)
+            },
+            _ => Err(BuildError::TooLarge { width: self.width as u64 + 2 * r as u64, height: self.height as u64 + 2 * r as u64 }),
+        }
+    }
+    
+    /// Erodes the foreground with a square structuring element
+    /// of (2·`radius` + 1)×(2·`radius` + 1) pixels.
+    /// The pixels outside the traced image are considered background.
+    /// 
+    /// Vanished components and holes merged with the background disappear from the hierarchy.
+    pub fn eroded(&self, radius: u32) -> Self {
+        self.try_eroded(radius).unwrap_or_else(|error| panic!("{error}"))
+    }
+    
+    /// Works like `eroded` but returns an error instead of panicking.
+    pub fn try_eroded(&self, radius: u32) -> Result<Self, BuildError> {
+        self.morphed(MorphologicalOperation::Erosion, radius, self.bounds())
+    }
+    
+    /// Erodes and then dilates the foreground, removing details smaller than the structuring element.
+    /// See `eroded` and `dilated`. The traced image bounds are kept.
+    pub fn opened(&self, radius: u32) -> Self {
+        self.try_opened(radius).unwrap_or_else(|error| panic!("{error}"))
+    }
+    
+    /// Works like `opened` but returns an error instead of panicking.
+    pub fn try_opened(&self, radius: u32) -> Result<Self, BuildError> {
+        self.morphed(MorphologicalOperation::Opening, radius, self.bounds())
+    }
+    
+    /// Dilates and then erodes the foreground, filling gaps smaller than the structuring element.
+    /// See `dilated` and `eroded`. The traced image bounds are kept.
+    /// 
+    /// Panics if the dilated foreground does not fit in `i32` coordinates, see `try_closed`.
+    pub fn closed(&self, radius: u32) -> Self {
+        self.try_closed(radius).unwrap_or_else(|error| panic!("{error}"))
+    }
+    
+    /// Works like `closed` but returns an error
+    /// if the dilated foreground does not fit in `i32` coordinates.
+    pub fn try_closed(&self, radius: u32) -> Result<Self, BuildError> {
+        self.morphed(MorphologicalOperation::Closing, radius, self.bounds())
+    }
+    
+    fn bounds(&self) -> Box2D<i32> {
+        let (left, top) = self.origin();
+        let (width, height) = self.dimensions();
+        Box2D::new(Point2D::new(left, top), Point2D::new(left + width, top + height))
+    }
+    
+    /// Applies the `operation` to the bands of the contours
+    /// and traces the result, which lies within the `bounds`.
+    /// The `bounds` become the traced image bounds.
+    fn morphed(&self, operation: MorphologicalOperation, radius: u32, bounds: Box2D<i32>) -> Result<Self, BuildError> {
+        let contours: Vec<_> = self.all_contours().collect();
+        let bands = orthopolygon_bands(contours.iter());
+        let morphed = morph_bands(operation, &bands, radius).ok_or_else(|| too_large(&bands, radius))?;
+        let mut collection = trace_bands(&morphed, self.connectivity)?;
+        (collection.left, collection.top) = (bounds.min.x, bounds.min.y);
+        (collection.width, collection.height) = (bounds.width(), bounds.height());
+        Ok(collection)
+    }
+}
+
+/// The error for the `bands` dilated by `radius` not fitting in `i32` coordinates.
+pub fn too_large(bands: &[Band], radius: u32) -> BuildError {
+    let (left, right) = bands.iter()
+        .flat_map(|band| &band.runs)
+        .fold((i64::MAX, i64::MIN), |(left, right), &(x0, x1)| (left.min(x0 as i64), right.max(x1 as i64)));
+    let height = bands.last().map_or(0, |last| last.y1 as i64 - bands[0].y0 as i64);
+    let margin = 2 * radius as u64;
+    BuildError::TooLarge { width: (right - left).max(0) as u64 + margin, height: height as u64 + margin }
+}
@@ -21,6 +21,13 @@ impl PointListBuilder {
         Self { point_list: Vec::new() }
     }
     
+    /// Creates a list starting with `count` placeholder points
+    /// that stand for open contour ends of the part of the image above a seam.
+    pub fn with_placeholders(count: usize) -> Self {
+        let point_list = (0..count).map(|_| PointListItem { x: 0, y: 0, next: NONE }).collect();
+        Self { point_list }
+    }
+    
     /// Adds a point that is not yet connected to anything.
     /// Returns its index.
     pub fn add(&mut self, x: i32, y: i32) -> usize {
@@ -54,6 +61,29 @@ impl PointListBuilder {
         self.point_list[previous].next = index;
     }
     
+    /// Appends the points of a `strip` list created by `with_placeholders`.
+    /// Its placeholders stand for the `seam_ends` points of this list.
+    /// 
+    /// Returns a function mapping point indices of the strip to indices in this list.
+    pub fn append_strip(&mut self, strip: Self, seam_ends: Vec<usize>) -> impl Fn(usize) -> usize + use<> {
+        let count = seam_ends.len();
+        let base = self.point_list.len();
+        let map = move |index: usize| if index < count { seam_ends[index] } else { index - count + base };
+        
+        let mut points = strip.point_list.into_iter();
+        // Placeholders that got their `next` in the strip pass it to the points they stand for
+        for (index, placeholder) in points.by_ref().take(count).enumerate() {
+            if placeholder.next != NONE {
+                self.point_list[map(index)].next = map(placeholder.next);
+            }
+        }
+        for point in points {
+            let next = if point.next != NONE { map(point.next) } else { NONE };
+            self.point_list.push(PointListItem { next, ..point });
+        }
+        map
+    }
+    
     /// Returns the constructed list.
     pub fn into(self) -> Vec<PointListItem> {
         self.point_list
@@ -0,0 +1,62 @@
+use euclid::default::Vector2D;
+use image::{GrayImage, ImageBuffer, Luma};
+use crate::geometry::{draw_orthopolygons, fill_orthopolygons, Orthopolygon, Orthopolygonlike};
+use super::{ImageContourCollection, Contour};
+
+impl ImageContourCollection {
+    /// Renders all the contours to an image of the traced image size
+    /// multiplied by `scale`. See `contours_to_image`.
+    /// 
+    /// With `scale` 1, the result is the binarized traced image.
+    pub fn to_image(&self, inverted: bool, scale: u32) -> GrayImage {
+        self.contours_to_image(self.all_contours(), inverted, scale)
+    }
+    
+    /// Renders the given `contours` of the collection to an image of the traced image size
+    /// multiplied by `scale`, each pixel becoming a `scale`×`scale` square.
+    /// 
+    /// The pixels inside the contours by the even-odd rule are white, and the others are black.
+    /// So, the holes of an outer contour are rendered only if they are among the `contours`.
+    /// If `inverted` is `true`, the colors are swapped, as in the `new` constructor.
+    /// 
+    /// Panics if `scale` is zero or the scaled image width or height does not fit in `i32`.
+    pub fn contours_to_image<'a>(&'a self, contours: impl Iterator<Item = Contour<'a>>, inverted: bool, scale: u32) -> GrayImage {
+        assert!(scale > 0, "Scale should be positive");
+        let (width, height) = self.dimensions();
+        // The scaled vertex coordinates lie within the scaled image size
+        let scale = i32::try_from(scale).ok()
+            .filter(|&scale| width.checked_mul(scale).is_some() && height.checked_mul(scale).is_some())
+            .expect("Scaled image size should fit in i32");
+        let (foreground, background) = if inverted { (0, 255) } else { (255, 0) };
+        let mut image = GrayImage::from_pixel((width * scale) as u32, (height * scale) as u32, Luma([background]));
+        
+        let origin = Vector2D::from(self.origin());
+        let orthopolygons: Vec<_> = contours
+            .map(|contour| Orthopolygon::new(contour.even_vertices().map(|vertex| (vertex - origin) * scale)))
+            .collect();
+        draw_orthopolygons(&mut image, |_| foreground, orthopolygons.iter());
+        image
+    }
+    
+    /// Creates a label image of the traced image size, where the pixels
+    /// of each connected component have a distinct nonzero label,
+    /// and the background pixels, including the holes, are zero.
+    /// 
+    /// The component of the n-th outer contour in `outer_contours` order is labeled n.
+    pub fn to_label_image(&self) -> ImageBuffer<Luma<u32>, Vec<u32>> {
+        let (left, top) = self.origin();
+        let (width, height) = self.dimensions();
+        let mut label_image = ImageBuffer::new(width as u32, height as u32);
+        
+        for (outer, label) in self.outer_contours().zip(1..) {
+            // The holes are filled out by the even-odd rule
+            let contours: Vec<_> = [outer].into_iter().chain(outer.children()).collect();
+            fill_orthopolygons(contours.iter(), |y, x0, x1| {
+                for x in x0..x1 {
+                    label_image.put_pixel((x - left) as u32, (y - top) as u32, Luma([label]));
+                }
+            });
+        }
+        label_image
+    }
+}
@@ -1,4 +1,4 @@
-use image::{buffer::Pixels, Luma};
+use std::iter;
 
 /// Marks the end of changes iteration.
 /// Is greater than any coordinate.
@@ -9,7 +9,7 @@ pub const END: i32 = i32::MAX;
 /// A _change_ occurs when the value of the pixel differs from the value of its neighbor.
 /// The edge pixels that have no neighbors are compared to `edge_value` instead.
 /// 
-/// Binary pixel values assumed — all non-zero `Luma<u8>` values are considered the same.
+/// Takes binary pixel values as `bool`s.
 /// 
 /// Coordinates correspond to positions between the pixels:
 /// - coordinate 0 is to the left of the 0th pixel,
@@ -19,34 +19,35 @@ pub const END: i32 = i32::MAX;
 /// 
 /// When there are no more changes, the iterator returns the `END` marker.
 /// It is greater than any coordinate for the convenience of comparison.
-pub struct RowChangeIter<'a> {
-    row: Option<Pixels<'a, Luma<u8>>>,
+pub struct RowChangeIter<Row> {
+    row: Option<Row>,
     edge_value: bool,
     previous: bool,
     x: i32,
 }
 
-impl<'a> RowChangeIter<'a> {
+impl RowChangeIter<iter::Empty<bool>> {
     /// A row with no changes. Used as a padding row.
     pub fn empty() -> Self {
         Self { row: None, edge_value: false, previous: false, x: 0 }
     }
-    
-    /// Consumes a row of pixels and returns an iterator over its changes.
-    pub fn from(row: Pixels<'a, Luma<u8>>, edge_value: bool) -> Self {
+}
+
+impl<Row: Iterator<Item = bool>> RowChangeIter<Row> {
+    /// Consumes a row of binary pixel values and returns an iterator over its changes.
+    pub fn from(row: Row, edge_value: bool) -> Self {
         Self { row: Some(row), edge_value, previous: edge_value, x: 0 }
     }
 }
 
-impl<'a> Iterator for RowChangeIter<'a> {
+impl<Row: Iterator<Item = bool>> Iterator for RowChangeIter<Row> {
     type Item = i32;
     
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(row) = &mut self.row {
             // performance-critical
-            while let Some(pixel) = row.next() {
+            for value in row.by_ref() {
                 let x = self.x;
-                let value = pixel[0] != 0;
                 let differs = value != self.previous;
                 self.previous = value;
                 self.x += 1;
@@ -68,13 +69,100 @@ impl<'a> Iterator for RowChangeIter<'a> {
     }
 }
 
+/// Iterates x coordinates in a row of bit-packed pixels where changes occur, from left to right.
+/// 
+/// Works the same way as `RowChangeIter`, but takes a row of 1-bit pixels
+/// packed 8 pixels per byte, the most significant bit first (as in PBM or fax TIFF files).
+/// Bit value 1 is considered white.
+/// 
+/// The changes are searched a 64-bit word at a time.
+pub struct PackedRowChangeIter<'a> {
+    row: Option<&'a[u8]>,
+    width: i32,
+    edge_value: bool,
+    /// The value of the last pixel of the current word.
+    previous: bool,
+    /// x coordinate of the first pixel of the current word.
+    word_x: i32,
+    /// Changes of the current word not yet returned.
+    /// The most significant bit corresponds to `word_x`.
+    changes: u64,
+}
+
+impl<'a> PackedRowChangeIter<'a> {
+    /// Takes a row of `width` packed pixels and returns an iterator over its changes.
+    /// The row should contain at least `width.div_ceil(8)` bytes.
+    pub fn from(row: &'a[u8], width: u32, edge_value: bool) -> Self {
+        debug_assert!(row.len() * 8 >= width as usize);
+        Self { row: Some(row), width: width as i32, edge_value, previous: edge_value, word_x: -WORD_BITS, changes: 0 }
+    }
+}
+
+impl<'a> Iterator for PackedRowChangeIter<'a> {
+    type Item = i32;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(row) = self.row {
+            // performance-critical
+            loop {
+                if self.changes != 0 {
+                    let offset = self.changes.leading_zeros();
+                    self.changes ^= HIGHEST_BIT >> offset;
+                    return Some(self.word_x + offset as i32);
+                }
+                
+                let word_x = self.word_x + WORD_BITS;
+                if word_x >= self.width {
+                    break;
+                }
+                self.word_x = word_x;
+                
+                // Each pixel is compared to the previous one by XOR-ing the word with itself
+                // shifted by one bit. Pixels past the end of the row are masked out
+                let word = read_word(row, word_x as usize / 8);
+                let pixel_count = (self.width - word_x).min(WORD_BITS) as u32;
+                let shifted = (word >> 1) | ((self.previous as u64) << (WORD_BITS - 1));
+                let mask = !(u64::MAX.checked_shr(pixel_count).unwrap_or(0));
+                self.changes = (word ^ shifted) & mask;
+                self.previous = (word >> (WORD_BITS as u32 - pixel_count)) & 1 != 0;
+            }
+            self.row = None;
+            if self.previous != self.edge_value {
+                return Some(self.width);
+            }
+        }
+        if self.word_x != END {
+            self.word_x = END;
+            Some(END)
+        } else {
+            None
+        }
+    }
+}
+
+/// Reads 8 bytes starting from `byte_index` as a big-endian word.
+/// Missing bytes past the end of the row are read as zeros.
+fn read_word(row: &[u8], byte_index: usize) -> u64 {
+    if let Some(bytes) = row.get(byte_index..byte_index + 8) {
+        u64::from_be_bytes(bytes.try_into().unwrap())
+    } else {
+        let mut bytes = [0; 8];
+        let available = &row[byte_index.min(row.len())..];
+        bytes[..available.len()].copy_from_slice(available);
+        u64::from_be_bytes(bytes)
+    }
+}
+
+const WORD_BITS: i32 = u64::BITS as i32;
+const HIGHEST_BIT: u64 = 1 << (WORD_BITS - 1);
+
 
 // ---------
 
 #[cfg(test)]
 mod tests {
     use test_case::test_case;
-    use ::image::GrayImage;
+    use ::image::{GrayImage, Luma};
     use super::*;
 
     #[test]
@@ -98,6 +186,43 @@ mod tests {
         let width = row_pixels.len() as u32;
         let image = GrayImage::from_vec(width, 1, row_pixels).unwrap();
         let row = image.rows().next().unwrap();
-        RowChangeIter::from(row, edge_value).collect()
+        RowChangeIter::from(row.map(|&Luma([value])| value != 0), edge_value).collect()
+    }
+    
+    #[test_case(&[], 0, false => vec![END])]
+    #[test_case(&[], 0, true => vec![END])]
+    #[test_case(&[0b1000_0000], 1, false => vec![0, 1, END])]
+    #[test_case(&[0b1000_0000], 1, true => vec![END])]
+    #[test_case(&[0b0111_1111], 1, true => vec![0, 1, END])]
+    #[test_case(&[0b1100_0101], 8, false => vec![0, 2, 5, 6, 7, 8, END])]
+    #[test_case(&[0b1100_0101], 8, true => vec![2, 5, 6, 7, END])]
+    #[test_case(&[0b1100_0101], 6, true => vec![2, 5, END])]
+    #[test_case(&[0, 0, 0, 0, 0, 0, 0, 0b0000_0001, 0b1000_0000], 65, false => vec![63, 65, END])]
+    #[test_case(&[0, 0, 0, 0, 0, 0, 0, 0b0000_0001, 0b0000_0000], 65, false => vec![63, 64, END])]
+    #[test_case(&[0, 0, 0, 0, 0, 0, 0, 0b0000_0000, 0b1000_0000], 65, true => vec![0, 64, END])]
+    fn test_packed_row(row: &[u8], width: u32, edge_value: bool) -> Vec<i32> {
+        PackedRowChangeIter::from(row, width, edge_value).collect()
+    }
+    
+    #[test]
+    fn test_packed_row_matches_pixel_row() {
+        // Pseudo-random rows of various widths
+        let mut seed = 12345u32;
+        for width in [1, 2, 7, 8, 9, 63, 64, 65, 127, 128, 129, 200] {
+            for &edge_value in [false, true].iter() {
+                let pixels: Vec<bool> = (0..width).map(|_| {
+                    seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                    seed & 0x1000 != 0
+                }).collect();
+                let mut packed = vec![0u8; (width as usize).div_ceil(8)];
+                for (x, &value) in pixels.iter().enumerate() {
+                    packed[x / 8] |= (value as u8) << (7 - x % 8);
+                }
+                
+                let expected: Vec<_> = RowChangeIter::from(pixels.into_iter(), edge_value).collect();
+                let actual: Vec<_> = PackedRowChangeIter::from(&packed, width, edge_value).collect();
+                assert_eq!(actual, expected, "width {width}, edge value {edge_value}");
+            }
+        }
     }
 }
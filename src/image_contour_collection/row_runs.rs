@@ -0,0 +1,89 @@
+use euclid::default::Box2D;
+use crate::geometry::{fill_orthopolygons, Orthopolygonlike, Band};
+use super::{ImageContourCollection, Connectivity, BuildError, check_dimensions};
+
+/// Foreground runs `x0..x1` of consecutive rows, each row sorted and without touching runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowRuns {
+    /// The y coordinate of the first row.
+    pub top: i32,
+    pub rows: Vec<Vec<(i32, i32)>>,
+}
+
+impl RowRuns {
+    /// Scans the pixels inside the `orthopolygons` (by the even-odd rule)
+    /// within the `bounds`.
+    pub fn of_orthopolygons<'a, Ortho>(orthopolygons: impl Iterator<Item = &'a Ortho>, bounds: Box2D<i32>) -> Self
+    where Ortho: Orthopolygonlike + 'a {
+        let height = (bounds.max.y as i64 - bounds.min.y as i64).max(0) as usize;
+        let mut rows = vec![Vec::new(); height];
+        fill_orthopolygons(orthopolygons, |y, x0, x1| {
+            let (x0, x1) = (x0.max(bounds.min.x), x1.min(bounds.max.x));
+            if bounds.min.y <= y && y < bounds.max.y && x0 < x1 {
+                let row: &mut Vec<(i32, i32)> = &mut rows[(y - bounds.min.y) as usize];
+                // Spans of different orthopolygons can touch
+                match row.last_mut() {
+                    Some(last) if last.1 == x0 => last.1 = x1,
+                    _ => row.push((x0, x1)),
+                }
+            }
+        });
+        Self { top: bounds.min.y, rows }
+    }
+    
+    /// Traces the runs within the `bounds`, which become the traced image bounds.
+    pub fn trace(&self, bounds: Box2D<i32>, connectivity: Connectivity) -> Result<ImageContourCollection, BuildError> {
+        let (width, height) = (bounds.max.x as i64 - bounds.min.x as i64, bounds.max.y as i64 - bounds.min.y as i64);
+        check_dimensions(width.max(0) as u64, height.max(0) as u64)?;
+        let rows = (bounds.min.y..bounds.max.y).map(|y| {
+            let index = y as i64 - self.top as i64;
+            let runs = usize::try_from(index).ok().and_then(|index| self.rows.get(index)).map_or(&[][..], |row| row);
+            runs.iter()
+                .map(|&(x0, x1)| (x0.max(bounds.min.x), x1.min(bounds.max.x)))
+                .filter(|(x0, x1)| x0 < x1)
+                .flat_map(|(x0, x1)| [x0 - bounds.min.x, x1 - bounds.min.x])
+                .collect::<Vec<_>>()
+        });
+        let collection = ImageContourCollection::from_row_changes(width.max(0) as u32, rows, connectivity)?;
+        Ok(collection.moved_to(bounds.min.x, bounds.min.y))
+    }
+}
+
+/// Traces the pixels given by `bands`, see `geometry::orthopolygon_bands`.
+/// The bounding box of the bands becomes the traced image bounds.
+/// 
+/// Each band is traced as a single row, and then the y coordinates
+/// of the points are mapped back, so tall bands cost no more than short ones.
+pub fn trace_bands(bands: &[Band], connectivity: Connectivity) -> Result<ImageContourCollection, BuildError> {
+    let mut ys: Vec<_> = bands.iter().flat_map(|band| [band.y0, band.y1]).collect();
+    ys.sort_unstable();
+    ys.dedup();
+    let runs = bands.iter().flat_map(|band| &band.runs);
+    let left = runs.clone().map(|&(x0, _)| x0).min().unwrap_or(0);
+    let right = runs.map(|&(_, x1)| x1).max().unwrap_or(0);
+    let width = right as i64 - left as i64;
+    let height = ys.last().map_or(0, |&bottom| bottom as i64 - ys[0] as i64);
+    check_dimensions(width as u64, height as u64)?;
+    
+    // Bands are sorted, there can be gaps between them
+    let mut band_index = 0;
+    let rows = ys.iter().take(ys.len().saturating_sub(1)).map(|&y| {
+        while band_index < bands.len() && bands[band_index].y1 <= y {
+            band_index += 1;
+        }
+        match bands.get(band_index) {
+            Some(band) if band.y0 <= y => band.runs.iter().flat_map(|&(x0, x1)| [x0 - left, x1 - left]).collect(),
+            _ => Vec::new(),
+        }
+    });
+    let mut collection = ImageContourCollection::from_row_changes(width as u32, rows, connectivity)?;
+    
+    for point in &mut collection.point_list {
+        point.x += left;
+        point.y = ys[point.y as usize];
+    }
+    collection.left = left;
+    collection.top = ys.first().cloned().unwrap_or(0);
+    collection.height = height as i32;
+    Ok(collection)
+}
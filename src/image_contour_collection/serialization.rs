@@ -0,0 +1,184 @@
+use std::io::{self, BufRead, BufWriter, Read, Write};
+use std::num::NonZeroUsize;
+use crate::geometry::Orthopolygonlike;
+use super::{ImageContourCollection, Connectivity, LoadError, check_dimensions};
+use super::hierarchy_builder::HierarchyItem;
+use super::point_list_builder::PointListItem;
+
+/// Identifies the file format.
+const MAGIC: &[u8; 4] = b"UMRC";
+/// Incremented on any incompatible change of the format.
+const VERSION: u8 = 1;
+
+impl ImageContourCollection {
+    /// Writes the collection in a compact binary format.
+    /// 
+    /// After the magic bytes `UMRC` and the version byte follow
+    /// the origin, the dimensions, the connectivity and the contour count,
+    /// then the contours in the hierarchy order.
+    /// Each contour is stored as the index of its parent,
+    /// the number of its even vertices and the vertices themselves.
+    /// The first vertex is relative to the head of the previous contour,
+    /// and each other vertex is relative to the previous one.
+    /// 
+    /// All the numbers are LEB128 variable-length integers,
+    /// the signed ones are zigzag-encoded.
+    pub fn save(&self, writer: impl Write) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        
+        write_signed(&mut writer, self.left as i64)?;
+        write_signed(&mut writer, self.top as i64)?;
+        write_unsigned(&mut writer, self.width as u64)?;
+        write_unsigned(&mut writer, self.height as u64)?;
+        let connectivity = match self.connectivity {
+            Connectivity::Eight => 8,
+            Connectivity::Four => 4,
+        };
+        write_unsigned(&mut writer, connectivity)?;
+        write_unsigned(&mut writer, self.contour_count() as u64)?;
+        
+        let mut previous = (self.left as i64, self.top as i64);
+        for id in 1..self.hierarchy.len() {
+            let contour = self.contour(id).unwrap();
+            write_unsigned(&mut writer, self.hierarchy[id].parent as u64)?;
+            write_unsigned(&mut writer, contour.even_vertices().count() as u64)?;
+            
+            let mut vertices = contour.even_vertices().map(|vertex| (vertex.x as i64, vertex.y as i64));
+            let head = vertices.next().unwrap();
+            write_signed(&mut writer, head.0 - previous.0)?;
+            write_signed(&mut writer, head.1 - previous.1)?;
+            let mut vertex = head;
+            for next in vertices {
+                write_signed(&mut writer, next.0 - vertex.0)?;
+                write_signed(&mut writer, next.1 - vertex.1)?;
+                vertex = next;
+            }
+            previous = head;
+        }
+        writer.flush()
+    }
+    
+    /// Reads a collection written by `save` from a `reader` holding nothing else,
+    /// e. g. a file wrapped in a `BufReader`.
+    /// 
+    /// Returns an error if the data is truncated or followed by other data,
+    /// or if the contours do not pass `validate`,
+    /// so that the contours of the loaded collection can be safely iterated.
+    pub fn load(mut reader: impl BufRead) -> Result<Self, LoadError> {
+        let mut header = [0; 5];
+        read_bytes(&mut reader, &mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(LoadError::UnknownFormat);
+        }
+        if header[4] != VERSION {
+            return Err(LoadError::UnsupportedVersion { version: header[4] });
+        }
+        
+        let (left, top) = (read_coordinate(&mut reader)?, read_coordinate(&mut reader)?);
+        let (width, height) = (read_unsigned(&mut reader)?, read_unsigned(&mut reader)?);
+        check_dimensions(width, height).map_err(|_| LoadError::Corrupt { reason: "image is too large" })?;
+        let (width, height) = (width as i32, height as i32);
+        let (right, bottom) = (left as i64 + width as i64, top as i64 + height as i64);
+        if right > i32::MAX as i64 || bottom > i32::MAX as i64 {
+            return Err(LoadError::Corrupt { reason: "image is too large" });
+        }
+        let connectivity = match read_unsigned(&mut reader)? {
+            8 => Connectivity::Eight,
+            4 => Connectivity::Four,
+            _ => return Err(LoadError::Corrupt { reason: "invalid connectivity" }),
+        };
+        let contour_count = read_unsigned(&mut reader)?;
+        
+        // The counts are not trusted for preallocation
+        let mut hierarchy = vec![HierarchyItem::default()];
+        let mut point_list = Vec::new();
+        let mut previous = (left as i64, top as i64);
+        for index in 1..=contour_count {
+            let parent = read_unsigned(&mut reader)?;
+            if parent >= index {
+                return Err(LoadError::Corrupt { reason: "parent does not precede its child" });
+            }
+            let vertex_count = read_unsigned(&mut reader)?;
+            if vertex_count < 2 {
+                return Err(LoadError::Corrupt { reason: "contour has less than two even vertices" });
+            }
+            
+            let head_point_index = point_list.len();
+            let mut vertex = previous;
+            for vertex_index in 0..vertex_count {
+                vertex.0 = vertex.0.saturating_add(read_signed(&mut reader)?);
+                vertex.1 = vertex.1.saturating_add(read_signed(&mut reader)?);
+                if !(left as i64 <= vertex.0 && vertex.0 <= right && top as i64 <= vertex.1 && vertex.1 <= bottom) {
+                    return Err(LoadError::Corrupt { reason: "vertex is out of the image bounds" });
+                }
+                if vertex_index == 0 {
+                    previous = vertex;
+                }
+                let next = if vertex_index + 1 < vertex_count { point_list.len() + 1 } else { head_point_index };
+                point_list.push(PointListItem { x: vertex.0 as i32, y: vertex.1 as i32, next });
+            }
+            hierarchy.push(HierarchyItem { head_point_index, parent: parent as usize, ..Default::default() });
+        }
+        
+        // Siblings are linked in the order of their indices
+        for index in (1..hierarchy.len()).rev() {
+            let parent = hierarchy[index].parent;
+            hierarchy[index].next_sibling = hierarchy[parent].first_child;
+            hierarchy[parent].first_child = NonZeroUsize::new(index);
+        }
+        
+        if !reader.fill_buf().map_err(LoadError::Io)?.is_empty() {
+            return Err(LoadError::TrailingData);
+        }
+        let collection = Self { left, top, width, height, connectivity, hierarchy, point_list };
+        collection.validate().map_err(|_| LoadError::Corrupt { reason: "contours are inconsistent" })?;
+        Ok(collection)
+    }
+}
+
+fn write_unsigned(writer: &mut impl Write, mut value: u64) -> io::Result<()> {
+    while value >= 0x80 {
+        writer.write_all(&[(value as u8) | 0x80])?;
+        value >>= 7;
+    }
+    writer.write_all(&[value as u8])
+}
+
+fn write_signed(writer: &mut impl Write, value: i64) -> io::Result<()> {
+    write_unsigned(writer, ((value << 1) ^ (value >> 63)) as u64)
+}
+
+fn read_bytes(reader: &mut impl Read, buffer: &mut [u8]) -> Result<(), LoadError> {
+    reader.read_exact(buffer).map_err(|error| match error.kind() {
+        io::ErrorKind::UnexpectedEof => LoadError::Truncated,
+        _ => LoadError::Io(error),
+    })
+}
+
+fn read_unsigned(reader: &mut impl Read) -> Result<u64, LoadError> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0];
+        read_bytes(reader, &mut byte)?;
+        let bits = (byte[0] & 0x7F) as u64;
+        if bits << shift >> shift != bits {
+            break;
+        }
+        value |= bits << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(LoadError::Corrupt { reason: "integer is too large" })
+}
+
+fn read_signed(reader: &mut impl Read) -> Result<i64, LoadError> {
+    let value = read_unsigned(reader)?;
+    Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+}
+
+fn read_coordinate(reader: &mut impl Read) -> Result<i32, LoadError> {
+    i32::try_from(read_signed(reader)?).map_err(|_| LoadError::Corrupt { reason: "coordinate is too large" })
+}
@@ -0,0 +1,128 @@
+use euclid::default::{Box2D, Point2D};
+use crate::geometry::Orthopolygonlike;
+use super::{ImageContourCollection, Contour};
+
+/// A uniform grid over the bounding boxes of the contours of a collection
+/// for fast hit-testing and rectangle queries.
+/// 
+/// Each grid cell lists the contours whose bounding boxes overlap it.
+/// The candidates found in the cells are then tested exactly.
+pub struct ContourIndex<'a> {
+    /// Contours in the depth-first order with their bounding boxes and depths.
+    entries: Vec<Entry<'a>>,
+    origin: Point2D<i32>,
+    cell_size: i32,
+    columns: usize,
+    rows: usize,
+    /// Entry indices for each cell, row by row.
+    cells: Vec<Vec<usize>>,
+}
+
+struct Entry<'a> {
+    contour: Contour<'a>,
+    bounding_box: Box2D<i32>,
+    /// 0 for the outermost contours.
+    depth: usize,
+}
+
+impl<'a> ContourIndex<'a> {
+    /// Builds an index of all the contours of the `collection`.
+    /// The cell size is chosen so that there is about one contour per cell.
+    pub fn new(collection: &'a ImageContourCollection) -> Self {
+        let (width, height) = collection.dimensions();
+        let count = collection.all_contours().count().max(1);
+        let cell_size = ((width as f64 * height as f64 / count as f64).sqrt() as i32).clamp(MIN_CELL_SIZE, MAX_CELL_SIZE);
+        Self::with_cell_size(collection, cell_size)
+    }
+    
+    /// Builds an index of all the contours of the `collection`
+    /// using square grid cells of the given size in pixels.
+    /// 
+    /// Panics if `cell_size` is not positive.
+    pub fn with_cell_size(collection: &'a ImageContourCollection, cell_size: i32) -> Self {
+        assert!(cell_size > 0, "Cell size should be positive");
+        let (left, top) = collection.origin();
+        let (width, height) = collection.dimensions();
+        let columns = (width / cell_size) as usize + 1;
+        let rows = (height / cell_size) as usize + 1;
+        
+        let mut index = Self {
+            entries: Vec::new(),
+            origin: Point2D::new(left, top),
+            cell_size, columns, rows,
+            cells: vec![Vec::new(); columns * rows],
+        };
+        
+        // Parents come before their children in the depth-first order
+        let mut depths = vec![0; collection.hierarchy.len()];
+        for contour in collection.all_contours() {
+            let parent = collection.hierarchy[contour.id()].parent;
+            let depth = if parent == 0 { 0 } else { depths[parent] + 1 };
+            depths[contour.id()] = depth;
+            
+            let bounding_box = contour.bounding_box();
+            let entry_index = index.entries.len();
+            index.entries.push(Entry { contour, bounding_box, depth });
+            for cell in index.cells_overlapping(bounding_box) {
+                index.cells[cell].push(entry_index);
+            }
+        }
+        index
+    }
+    
+    /// Returns the innermost contour enclosing the pixel with coordinates `point`,
+    /// i. e. the square from (x, y) to (x + 1, y + 1),
+    /// or `None` if the pixel is in the background outside all the contours.
+    /// 
+    /// A pixel inside a hole gets the hole contour.
+    pub fn contour_at(&self, point: Point2D<i32>) -> Option<Contour<'a>> {
+        let pixel = Box2D::new(point, Point2D::new(point.x.saturating_add(1), point.y.saturating_add(1)));
+        let cell = self.cells_overlapping(Box2D::new(point, point)).next()?;
+        self.cells[cell].iter()
+            .map(|&entry_index| &self.entries[entry_index])
+            .filter(|entry| entry.bounding_box.intersects(&pixel) && entry.contour.overlaps_box(pixel))
+            .max_by_key(|entry| entry.depth)
+            .map(|entry| entry.contour)
+    }
+    
+    /// Iterates the contours whose enclosed area overlaps the `rect`
+    /// in the depth-first order, parents before their children.
+    pub fn contours_in_rect(&self, rect: Box2D<i32>) -> impl Iterator<Item = Contour<'a>> + '_ {
+        let mut entry_indices: Vec<_> = self.cells_overlapping(rect)
+            .flat_map(|cell| self.cells[cell].iter().cloned())
+            .collect();
+        entry_indices.sort_unstable();
+        entry_indices.dedup();
+        
+        entry_indices.into_iter()
+            .map(|entry_index| &self.entries[entry_index])
+            .filter(move |entry| entry.bounding_box.intersects(&rect) && entry.contour.overlaps_box(rect))
+            .map(|entry| entry.contour)
+    }
+    
+    /// Iterates indices of the cells overlapping the `rect`.
+    /// The parts of the `rect` outside the grid are ignored.
+    fn cells_overlapping(&self, rect: Box2D<i32>) -> impl Iterator<Item = usize> + use<> {
+        let cell = |coordinate: i32, origin: i32, count: usize|
+            ((coordinate as i64 - origin as i64) / self.cell_size as i64).clamp(0, count as i64 - 1) as usize;
+        let is_outside = rect.max.x < self.origin.x || rect.max.y < self.origin.y
+            || rect.min.x as i64 > self.origin.x as i64 + self.columns as i64 * self.cell_size as i64
+            || rect.min.y as i64 > self.origin.y as i64 + self.rows as i64 * self.cell_size as i64;
+        
+        let columns = cell(rect.min.x, self.origin.x, self.columns)..=cell(rect.max.x, self.origin.x, self.columns);
+        let rows = cell(rect.min.y, self.origin.y, self.rows)..=cell(rect.max.y, self.origin.y, self.rows);
+        let column_count = self.columns;
+        rows.filter(move |_| !is_outside)
+            .flat_map(move |row| columns.clone().map(move |column| row * column_count + column))
+    }
+}
+
+impl ImageContourCollection {
+    /// Builds a spatial index of the contours, see `ContourIndex`.
+    pub fn spatial_index(&self) -> ContourIndex<'_> {
+        ContourIndex::new(self)
+    }
+}
+
+const MIN_CELL_SIZE: i32 = 16;
+const MAX_CELL_SIZE: i32 = 1024;
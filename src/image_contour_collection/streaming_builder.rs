@@ -0,0 +1,252 @@
+use std::{fmt, iter, mem};
+use super::{ImageContourCollection, Connectivity, BuildError, check_dimensions};
+use super::contour_collection_builder::ContourCollectionBuilder;
+use super::row_changes::{RowChangeIter, PackedRowChangeIter, END};
+use super::row_pair_changes::RowPairChangeIter;
+
+/// Builds an `ImageContourCollection` from image rows pushed one at a time,
+/// top to bottom, e. g. straight from an image decoder.
+/// 
+/// Only the changes of the last pushed row are stored,
+/// so the whole image never has to be in memory.
+/// 
+/// The height of the image is the number of the pushed rows.
+pub struct StreamingBuilder {
+    width: u32,
+    height: u32,
+    inverted: bool,
+    builder: ContourCollectionBuilder,
+    
+    // Row changes are stored in two buffers of fixed capacity to avoid allocation
+    top_changes: Vec<i32>,
+    bottom_changes: Vec<i32>,
+    
+    /// A buffer for changes converted from runs.
+    run_changes: Vec<i32>,
+}
+
+/// Describes what is wrong with a row given as changes or runs.
+/// `row` is the index of the row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidRowError {
+    /// The change at `index` is not greater than the previous one.
+    Unsorted { row: u32, index: usize },
+    /// The number of changes is odd, so the last foreground run is not closed.
+    OddChangeCount { row: u32 },
+    /// A change or the end of a run lies outside of the row.
+    OutOfBounds { row: u32, x: i64 },
+}
+
+impl fmt::Display for InvalidRowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsorted { row, index } => write!(f, "Row {row}: change {index} is out of order"),
+            Self::OddChangeCount { row } => write!(f, "Row {row}: odd number of changes"),
+            Self::OutOfBounds { row, x } => write!(f, "Row {row}: x coordinate {x} is out of bounds"),
+        }
+    }
+}
+
+impl std::error::Error for InvalidRowError { }
+
+impl StreamingBuilder {
+    /// Creates a builder for an image of the given `width`.
+    /// 
+    /// `inverted` and `connectivity` have the same meaning as in `ImageContourCollection::new`.
+    /// 
+    /// Panics if the `width` does not fit in `i32`, see `try_new`.
+    pub fn new(width: u32, inverted: bool, connectivity: Connectivity) -> Self {
+        Self::try_new(width, inverted, connectivity).unwrap_or_else(|error| panic!("{error}"))
+    }
+    
+    /// Works like `new`, but returns an error instead of panicking.
+    pub fn try_new(width: u32, inverted: bool, connectivity: Connectivity) -> Result<Self, BuildError> {
+        check_dimensions(width, 0u32)?;
+        let capacity = width as usize + 2;
+        let mut bottom_changes = Vec::with_capacity(capacity);
+        
+        // Add a padding row to the top of the image
+        bottom_changes.extend(RowChangeIter::empty());
+        
+        Ok(Self {
+            width, height: 0, inverted,
+            builder: ContourCollectionBuilder::new(connectivity),
+            top_changes: Vec::with_capacity(capacity),
+            bottom_changes,
+            run_changes: Vec::new(),
+        })
+    }
+    
+    /// Creates a builder for a horizontal strip of an image starting at row `y`.
+    /// `row_above` is the last row of the strip above, it is not added.
+    /// 
+    /// The contours crossing the seam are left open,
+    /// see `ContourCollectionBuilder::new_strip`.
+    pub(super) fn new_strip(width: u32, inverted: bool, connectivity: Connectivity, y: u32, row_above: impl Iterator<Item = bool>) -> Self {
+        let capacity = width as usize + 2;
+        let mut bottom_changes = Vec::with_capacity(capacity);
+        bottom_changes.extend(RowChangeIter::from(row_above, inverted));
+        let seam_end_count = bottom_changes.len() - 1;
+        
+        Self {
+            width, height: y, inverted,
+            builder: ContourCollectionBuilder::new_strip(connectivity, seam_end_count),
+            top_changes: Vec::with_capacity(capacity),
+            bottom_changes,
+            run_changes: Vec::new(),
+        }
+    }
+    
+    /// Number of rows pushed so far.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    
+    /// Adds the next row of 8-bit grayscale pixels.
+    /// All non-zero pixel values are considered white.
+    /// 
+    /// Panics if the length of the row differs from the width.
+    pub fn push_row(&mut self, row: &[u8]) {
+        assert_eq!(row.len(), self.width as usize, "Row length differs from the width");
+        let row_changes = RowChangeIter::from(row.iter().map(|&value| value != 0), self.inverted);
+        self.push_row_changes(row_changes);
+    }
+    
+    /// Adds the next row of binary pixel values (`true` is white).
+    /// 
+    /// Panics if the length of the row differs from the width.
+    pub fn push_binary_row<Row>(&mut self, row: Row)
+        where Row: IntoIterator<Item = bool>, Row::IntoIter: ExactSizeIterator {
+        let row = row.into_iter();
+        assert_eq!(row.len(), self.width as usize, "Row length differs from the width");
+        self.push_binary_pixels(row);
+    }
+    
+    /// Works like `push_binary_row`, but the length of the row is not checked.
+    /// For the callers that take the rows from an image of the same width.
+    pub(super) fn push_binary_pixels(&mut self, row: impl Iterator<Item = bool>) {
+        let row_changes = RowChangeIter::from(row, self.inverted);
+        self.push_row_changes(row_changes);
+    }
+    
+    /// Adds the next row of 1-bit pixels packed 8 pixels per byte,
+    /// the most significant bit first. Bit value 1 is considered white.
+    /// 
+    /// Panics if the row is shorter than `width.div_ceil(8)` bytes.
+    pub fn push_packed_row(&mut self, row: &[u8]) {
+        let row_length = (self.width as usize).div_ceil(8);
+        assert!(row.len() >= row_length, "Row is too short for the width");
+        let row_changes = PackedRowChangeIter::from(&row[..row_length], self.width, self.inverted);
+        self.push_row_changes(row_changes);
+    }
+    
+    /// Adds the next row given as x coordinates of its changes, i. e.
+    /// the start of the first foreground run, the end of the first foreground run,
+    /// the start of the second one, and so on.
+    /// The changes describe the foreground directly, so `inverted` is not applied.
+    /// 
+    /// The changes should be in strictly ascending order, their number should be even,
+    /// and all of them should lie within `0..=width`.
+    /// Otherwise, an error is returned and the row is not added.
+    pub fn push_change_row(&mut self, changes: &[i32]) -> Result<(), InvalidRowError> {
+        let row = self.height;
+        if !changes.len().is_multiple_of(2) {
+            return Err(InvalidRowError::OddChangeCount { row });
+        }
+        for (index, &x) in changes.iter().enumerate() {
+            if x < 0 || x > self.width as i32 {
+                return Err(InvalidRowError::OutOfBounds { row, x: x as i64 });
+            }
+            if index > 0 && x <= changes[index - 1] {
+                return Err(InvalidRowError::Unsorted { row, index });
+            }
+        }
+        self.push_row_changes(changes.iter().cloned().chain(iter::once(END)));
+        Ok(())
+    }
+    
+    /// Adds the next row given as lengths of alternating background and foreground runs
+    /// starting with a background run, which can have zero length (as in CCITT fax coding).
+    /// The runs describe the foreground directly, so `inverted` is not applied.
+    /// 
+    /// Zero-length runs are allowed. If the runs end before the end of the row,
+    /// the rest of the row is considered background.
+    /// If the runs end past the end of the row, an error is returned and the row is not added.
+    pub fn push_run_row(&mut self, runs: &[u32]) -> Result<(), InvalidRowError> {
+        let row = self.height;
+        let mut changes = mem::take(&mut self.run_changes);
+        changes.clear();
+        
+        let mut x = 0;
+        for (index, &run) in runs.iter().enumerate() {
+            x += run as i64;
+            if x > self.width as i64 {
+                self.run_changes = changes;
+                return Err(InvalidRowError::OutOfBounds { row, x });
+            }
+            // There is no change after the last background run
+            let is_foreground = index % 2 == 1;
+            if is_foreground || index + 1 < runs.len() {
+                // A zero-length run cancels the previous change
+                if changes.last() == Some(&(x as i32)) {
+                    changes.pop();
+                } else {
+                    changes.push(x as i32);
+                }
+            }
+        }
+        
+        self.push_row_changes(changes.iter().cloned().chain(iter::once(END)));
+        self.run_changes = changes;
+        Ok(())
+    }
+    
+    /// Adds a padding row to the bottom of the image
+    /// and returns the constructed collection.
+    /// 
+    /// Panics if the collection cannot be built, see `try_finish`.
+    pub fn finish(self) -> ImageContourCollection {
+        self.try_finish().unwrap_or_else(|error| panic!("{error}"))
+    }
+    
+    /// Works like `finish`, but returns an error instead of panicking,
+    /// e. g. if too many rows were pushed.
+    pub fn try_finish(self) -> Result<ImageContourCollection, BuildError> {
+        check_dimensions(self.width, self.height)?;
+        let (width, height) = (self.width as i32, self.height as i32);
+        self.into_strip(true).into(width, height)
+    }
+    
+    /// Returns the builder of a strip of the image.
+    /// If the strip `is_last`, adds a padding row to the bottom of the image first.
+    pub(super) fn into_strip(mut self, is_last: bool) -> ContourCollectionBuilder {
+        if is_last {
+            let y = self.height;
+            self.scan_row(y, RowChangeIter::empty());
+        }
+        self.builder
+    }
+    
+    /// Adds the changes of the next row. The changes should end with `END`.
+    fn push_row_changes(&mut self, row_changes: impl Iterator<Item = i32>) {
+        let y = self.height;
+        self.scan_row(y, row_changes);
+        self.height += 1;
+    }
+    
+    fn scan_row(&mut self, y: u32, row_changes: impl Iterator<Item = i32>) {
+        // Swap the buffers so the old bottom row becomes the new top row.
+        // Fill new bottom row with new row changes
+        mem::swap(&mut self.top_changes, &mut self.bottom_changes);
+        self.bottom_changes.clear();
+        self.bottom_changes.extend(row_changes);
+        
+        // Look for changes in two adjacent image rows
+        let row_pair_changes = RowPairChangeIter::new(&self.top_changes, &self.bottom_changes);
+        
+        for change in row_pair_changes {
+            // Update the point list and contour hierarchy
+            self.builder.add_row_pair_change(y as i32, change);
+        }
+    }
+}
@@ -1,10 +1,12 @@
 use std::num::NonZeroUsize;
-use image::Luma;
-use euclid::default::Point2D;
+use image::{imageops, DynamicImage, Luma};
+use euclid::default::{Box2D, Point2D};
 use itertools::Itertools;
 use test_case::test_case;
-use crate::test_images::get_test_images;
-use crate::geometry::{draw_orthopolygons, Orthopolygonlike, PointPosition, Polygonlike};
+use crate::test_images::{get_test_images, get_test_image};
+use euclid::{point2, vec2};
+use crate::geometry::{draw_orthopolygons, BooleanOperation, Orientation, Orthopolygon, Orthopolygonlike, Polygon, Polygonlike, PointPosition, Moments, MorphologicalOperation};
+use std::{iter, panic};
 use super::*;
 
 #[test_case(
@@ -89,7 +91,76 @@ fn test_small_images(
     expected_point_list: Vec<PointListItem>, expected_hierarchy: Vec<HierarchyItem>
 ) {
     let image = GrayImage::from_vec(width, height, image_pixels).unwrap();
-    let actual = ImageContourCollection::new(&image, false);
+    let actual = ImageContourCollection::new(&image, false, Connectivity::Eight);
+    assert_eq!(actual.dimensions(), (width as i32, height as i32));
+    assert_eq!(actual.point_list, expected_point_list);
+    assert_eq!(actual.hierarchy, expected_hierarchy);
+}
+
+#[test_case(
+    2, 2, vec![
+        1, 0,
+        0, 1,
+    ],
+    vec![
+        /* 0 */ PointListItem { x: 0, y: 0, next: 1 },
+        /* 1 */ PointListItem { x: 1, y: 1, next: 0 },
+        /* 2 */ PointListItem { x: 1, y: 1, next: 3 },
+        /* 3 */ PointListItem { x: 2, y: 2, next: 2 },
+    ],
+    vec![
+        /* 0 */ root(NonZeroUsize::new(1)),
+        /* 1 */ hier(0, 0, NonZeroUsize::new(2), None),
+        /* 2 */ hier(2, 0, None, None),
+    ]
+)]
+#[test_case(
+    2, 2, vec![
+        0, 1,
+        1, 0,
+    ],
+    vec![
+        /* 0 */ PointListItem { x: 1, y: 0, next: 2 },
+        /* 1 */ PointListItem { x: 0, y: 1, next: 3 },
+        /* 2 */ PointListItem { x: 2, y: 1, next: 0 },
+        /* 3 */ PointListItem { x: 1, y: 2, next: 1 },
+    ],
+    vec![
+        /* 0 */ root(NonZeroUsize::new(1)),
+        /* 1 */ hier(0, 0, NonZeroUsize::new(2), None),
+        /* 2 */ hier(1, 0, None, None),
+    ]
+)]
+#[test_case(
+    3, 3, vec![
+        0, 1, 0,
+        1, 0, 1,
+        0, 1, 0,
+    ],
+    vec![
+        /* 0 */ PointListItem { x: 1, y: 0, next: 2 },
+        /* 1 */ PointListItem { x: 0, y: 1, next: 4 },
+        /* 2 */ PointListItem { x: 2, y: 1, next: 0 },
+        /* 3 */ PointListItem { x: 2, y: 1, next: 6 },
+        /* 4 */ PointListItem { x: 1, y: 2, next: 1 },
+        /* 5 */ PointListItem { x: 1, y: 2, next: 7 },
+        /* 6 */ PointListItem { x: 3, y: 2, next: 3 },
+        /* 7 */ PointListItem { x: 2, y: 3, next: 5 },
+    ],
+    vec![
+        /* 0 */ root(NonZeroUsize::new(1)),
+        /* 1 */ hier(0, 0, NonZeroUsize::new(2), None),
+        /* 2 */ hier(1, 0, NonZeroUsize::new(3), None),
+        /* 3 */ hier(3, 0, NonZeroUsize::new(4), None),
+        /* 4 */ hier(5, 0, None, None),
+    ]
+)]
+fn test_small_images_4_connected(
+    width: u32, height: u32, image_pixels: Vec<u8>,
+    expected_point_list: Vec<PointListItem>, expected_hierarchy: Vec<HierarchyItem>
+) {
+    let image = GrayImage::from_vec(width, height, image_pixels).unwrap();
+    let actual = ImageContourCollection::new(&image, false, Connectivity::Four);
     assert_eq!(actual.dimensions(), (width as i32, height as i32));
     assert_eq!(actual.point_list, expected_point_list);
     assert_eq!(actual.hierarchy, expected_hierarchy);
@@ -97,7 +168,7 @@ fn test_small_images(
 
 #[test]
 fn test_hierarchy_consistency() {
-    test_all_images(|testcase, _, _, contour_collection| {
+    test_all_images(|testcase, _, _, _, contour_collection| {
         let h = contour_collection.hierarchy;
         let mut is_visited = vec![false; h.len()];
         
@@ -129,14 +200,14 @@ fn test_hierarchy_consistency() {
 
 #[test]
 fn test_contour_folding() {
-    test_all_images(|testcase, _, _, contour_collection| {
+    test_all_images(|testcase, _, _, connectivity, contour_collection| {
         let (width, height) = contour_collection.dimensions();
         for contour in contour_collection.all_contours() {
             if let Some(parent) = contour.parent() {
                 for point in contour.vertices() {
                     let is_ok = match parent.get_point_position(point) {
                         PointPosition::Inside => true,
-                        PointPosition::Vertex => parent.is_outer(),
+                        PointPosition::Vertex => parent.is_outer() == (connectivity == Connectivity::Eight),
                         PointPosition::Outside | PointPosition::Edge => false,
                     };
                     assert!(is_ok, "{testcase}: a contour point is outside its parent contour");
@@ -153,34 +224,838 @@ fn test_contour_folding() {
 
 #[test]
 fn rasterization() {
-    test_all_images(|testcase, image, inverted, contour_collection| {
+    test_all_images(|testcase, image, inverted, _, contour_collection| {
+        let canvas = contour_collection.to_image(inverted, 1);
+        
+        let mut pixels = canvas.pixels().zip_eq(image.pixels());
+        let are_equal = pixels.all(|(&Luma([actual]), &Luma([expected]))| (actual != 0) == (expected != 0));
+        
+        assert!(are_equal, "{testcase}: rasterized contours differ from the original image");
+    })
+}
+
+#[test]
+fn test_scaled_rasterization() {
+    let image = get_test_image("art_50x50_dragon").unwrap();
+    let rect = Rect { x: 10, y: 20, width: 25, height: 15 };
+    let contour_collection = ImageContourCollection::new_in_rect(&image, rect, true, Connectivity::Eight);
+    let canvas = contour_collection.to_image(true, 3);
+    assert_eq!(canvas.dimensions(), (75, 45));
+    for (x, y, &Luma([value])) in canvas.enumerate_pixels() {
+        let &Luma([expected]) = image.get_pixel(x / 3 + 10, y / 3 + 20);
+        assert_eq!(value != 0, expected != 0, "Pixel ({x}, {y})");
+    }
+}
+
+#[test_case(1 << 30; "over i32")]
+#[test_case(u32::MAX; "over u32")]
+#[should_panic(expected = "Scaled image size should fit in i32")]
+fn test_rasterization_with_too_large_scale(scale: u32) {
+    let image = GrayImage::from_vec(2, 1, vec![255, 0]).unwrap();
+    ImageContourCollection::new(&image, false, Connectivity::Eight).to_image(false, scale);
+}
+
+#[test]
+fn test_rasterization_of_contour_subset() {
+    test_all_images(|testcase, _, _, _, contour_collection| {
+        // Outer contours only, so the holes are filled
+        let canvas = contour_collection.contours_to_image(contour_collection.outermost_contours(), false, 2);
+        let expected = contour_collection.filtered(|contour| contour.is_outer()).to_image(false, 2);
+        assert_eq!(canvas, expected, "{testcase}");
+    })
+}
+
+#[test]
+fn test_binarization_of_binary_images() {
+    test_all_images(|testcase, image, inverted, connectivity, contour_collection| {
+        if connectivity != Connectivity::Eight {
+            return;
+        }
+        for binarization in [Binarization::Threshold(127), Binarization::Otsu] {
+            let actual = ImageContourCollection::from_image(image, binarization, inverted, connectivity);
+            let are_equal = actual.point_list == contour_collection.point_list
+                && actual.hierarchy == contour_collection.hierarchy;
+            assert!(are_equal, "{testcase}: {binarization:?} binarization changed the contours");
+        }
+    })
+}
+
+#[test]
+fn test_packed_images() {
+    test_all_images(|testcase, image, inverted, connectivity, contour_collection| {
+        if connectivity != Connectivity::Eight {
+            return;
+        }
+        // Use a stride with some extra bytes
+        let (width, height) = image.dimensions();
+        let stride = (width as usize).div_ceil(8) + 3;
+        let mut buffer = vec![0u8; stride * height as usize];
+        for (x, y, &Luma([value])) in image.enumerate_pixels() {
+            if value != 0 {
+                buffer[y as usize * stride + x as usize / 8] |= 0x80 >> (x % 8);
+            }
+        }
+        
+        let actual = ImageContourCollection::from_packed(&buffer, width, height, stride, inverted, connectivity);
+        let are_equal = actual.point_list == contour_collection.point_list
+            && actual.hierarchy == contour_collection.hierarchy;
+        assert!(are_equal, "{testcase}: contours of the packed image differ");
+    })
+}
+
+#[test]
+fn test_streaming_builder() {
+    test_all_images(|testcase, image, inverted, connectivity, contour_collection| {
+        let (width, height) = image.dimensions();
+        let mut builder = StreamingBuilder::new(width, inverted, connectivity);
+        for row in image.as_raw().chunks_exact(width as usize) {
+            builder.push_row(row);
+        }
+        assert_eq!(builder.height(), height);
+        
+        let actual = builder.finish();
+        let are_equal = actual.dimensions() == contour_collection.dimensions()
+            && actual.point_list == contour_collection.point_list
+            && actual.hierarchy == contour_collection.hierarchy;
+        assert!(are_equal, "{testcase}: contours built row by row differ");
+    })
+}
+
+#[test]
+fn test_streaming_builder_without_rows() {
+    let actual = StreamingBuilder::new(10, false, Connectivity::Eight).finish();
+    assert_eq!(actual.dimensions(), (10, 0));
+    assert_eq!(actual.point_list, vec![]);
+    assert_eq!(actual.hierarchy, vec![root(None)]);
+}
+
+#[test_case(3)]
+#[test_case(5)]
+#[should_panic(expected = "Row length differs from the width")]
+fn test_streaming_builder_binary_row_of_wrong_length(length: usize) {
+    let mut builder = StreamingBuilder::new(4, false, Connectivity::Eight);
+    builder.push_binary_row(iter::repeat_n(true, length));
+}
+
+#[test]
+fn test_streaming_builder_rejects_row_before_adding_it() {
+    let mut builder = StreamingBuilder::new(4, false, Connectivity::Eight);
+    builder.push_binary_row([true, false, false, true]);
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| builder.push_binary_row([true; 5])));
+    assert!(result.is_err());
+    assert_eq!(builder.height(), 1);
+    
+    builder.push_binary_row([false, true, true, false]);
+    let actual = builder.finish();
+    let expected = ImageContourCollection::from_row_changes(4, [vec![0, 1, 3, 4], vec![1, 3]], Connectivity::Eight).unwrap();
+    assert_eq!(actual.point_list, expected.point_list);
+    assert_eq!(actual.hierarchy, expected.hierarchy);
+}
+
+#[test]
+fn test_row_changes_and_runs() {
+    test_all_images(|testcase, image, inverted, connectivity, contour_collection| {
+        let change_rows: Vec<Vec<i32>> = image.rows()
+            .map(|row| {
+                let foreground = row.map(|&Luma([value])| (value != 0) != inverted);
+                let mut changes = Vec::new();
+                let mut previous = false;
+                for (x, value) in foreground.enumerate() {
+                    if value != previous {
+                        changes.push(x as i32);
+                        previous = value;
+                    }
+                }
+                if previous {
+                    changes.push(image.width() as i32);
+                }
+                changes
+            })
+            .collect();
+        let run_rows: Vec<Vec<u32>> = change_rows.iter()
+            .map(|changes| iter::once(0).chain(changes.iter().cloned())
+                .tuple_windows()
+                .map(|(x0, x1)| (x1 - x0) as u32)
+                .collect())
+            .collect();
+        
+        let from_changes = ImageContourCollection::from_row_changes(image.width(), &change_rows, connectivity).unwrap();
+        let from_runs = ImageContourCollection::from_row_runs(image.width(), &run_rows, connectivity).unwrap();
+        for (actual, source) in [(from_changes, "changes"), (from_runs, "runs")] {
+            let are_equal = actual.dimensions() == contour_collection.dimensions()
+                && actual.point_list == contour_collection.point_list
+                && actual.hierarchy == contour_collection.hierarchy;
+            assert!(are_equal, "{testcase}: contours built from {source} differ");
+        }
+    })
+}
+
+#[test_case(vec![vec![1, 3], vec![2, 2]] => Err(BuildError::InvalidRow(InvalidRowError::Unsorted { row: 1, index: 1 })))]
+#[test_case(vec![vec![1, 3], vec![3, 1]] => Err(BuildError::InvalidRow(InvalidRowError::Unsorted { row: 1, index: 1 })))]
+#[test_case(vec![vec![1, 3, 4]] => Err(BuildError::InvalidRow(InvalidRowError::OddChangeCount { row: 0 })))]
+#[test_case(vec![vec![], vec![-1, 2]] => Err(BuildError::InvalidRow(InvalidRowError::OutOfBounds { row: 1, x: -1 })))]
+#[test_case(vec![vec![0, 5]] => Err(BuildError::InvalidRow(InvalidRowError::OutOfBounds { row: 0, x: 5 })))]
+#[test_case(vec![vec![0, 4], vec![1, 2]] => Ok(1))]
+#[test_case(vec![vec![0, 1, 3, 4], vec![]] => Ok(2))]
+fn test_invalid_row_changes(rows: Vec<Vec<i32>>) -> Result<usize, BuildError> {
+    ImageContourCollection::from_row_changes(4, rows, Connectivity::Eight)
+        .map(|collection| collection.all_contours().count())
+}
+
+#[test_case(vec![vec![1, 2, 1, 1]] => Err(BuildError::InvalidRow(InvalidRowError::OutOfBounds { row: 0, x: 5 })))]
+#[test_case(vec![vec![0, 4], vec![0, 0, 1, 2, 0, 1]] => Ok(vec![
+    PointListItem { x: 0, y: 0, next: 2 },
+    PointListItem { x: 1, y: 1, next: 0 },
+    PointListItem { x: 4, y: 2, next: 1 },
+]))]
+#[test_case(vec![vec![2], vec![0, 0, 0, 0]] => Ok(vec![]))]
+fn test_row_runs(rows: Vec<Vec<u32>>) -> Result<Vec<PointListItem>, BuildError> {
+    ImageContourCollection::from_row_runs(4, rows, Connectivity::Eight)
+        .map(|collection| collection.point_list)
+}
+
+#[test]
+fn test_parallel_strips() {
+    test_all_images(|testcase, image, inverted, connectivity, contour_collection| {
+        let strip_counts = [2, 3, 16, image.height() as usize];
+        for strip_count in strip_counts.into_iter().filter(|&count| count <= 256) {
+            let actual = ImageContourCollection::new_parallel(image, inverted, connectivity, strip_count);
+            let are_equal = actual.dimensions() == contour_collection.dimensions()
+                && actual.point_list == contour_collection.point_list
+                && actual.hierarchy == contour_collection.hierarchy;
+            assert!(are_equal, "{testcase}: contours traced in {strip_count} strips differ");
+        }
+    })
+}
+
+#[test_case(3, 4, vec![1, 1, 1, 1, 0, 1, 1, 0, 0, 0, 0, 1])]
+#[test_case(7, 5, vec![
+    1, 1, 1, 1, 1, 1, 1,
+    1, 0, 0, 0, 0, 0, 1,
+    1, 0, 1, 1, 1, 0, 1,
+    1, 0, 1, 0, 1, 0, 1,
+    1, 0, 1, 1, 1, 0, 1,
+])]
+#[test_case(4, 0, vec![])]
+fn test_parallel_strips_of_small_images(width: u32, height: u32, image_pixels: Vec<u8>) {
+    let image = GrayImage::from_vec(width, height, image_pixels).unwrap();
+    let expected = ImageContourCollection::new(&image, false, Connectivity::Eight);
+    for strip_count in 0..=height as usize + 1 {
+        let actual = ImageContourCollection::new_parallel(&image, false, Connectivity::Eight, strip_count);
+        assert_eq!(actual.dimensions(), expected.dimensions());
+        assert_eq!(actual.point_list, expected.point_list);
+        assert_eq!(actual.hierarchy, expected.hierarchy);
+    }
+}
+
+#[test]
+fn test_sub_images() {
+    test_all_images(|testcase, image, inverted, connectivity, _| {
+        let (width, height) = image.dimensions();
+        let rects = [
+            Rect { x: 0, y: 0, width, height },
+            Rect { x: width / 3, y: height / 4, width: width / 2, height: height / 3 },
+            Rect { x: width / 2, y: 0, width: width - width / 2, height: 1.min(height) },
+        ];
+        for rect in rects {
+            let actual = ImageContourCollection::new_in_rect(image, rect, inverted, connectivity);
+            let cropped = image.view(rect.x, rect.y, rect.width, rect.height).to_image();
+            let expected = ImageContourCollection::new(&cropped, inverted, connectivity);
+            
+            let shifted_points: Vec<_> = expected.point_list.iter()
+                .map(|&PointListItem { x, y, next }| PointListItem { x: x + rect.x as i32, y: y + rect.y as i32, next })
+                .collect();
+            let are_equal = actual.origin() == (rect.x as i32, rect.y as i32)
+                && actual.dimensions() == expected.dimensions()
+                && actual.point_list == shifted_points
+                && actual.hierarchy == expected.hierarchy;
+            assert!(are_equal, "{testcase}: contours of {rect:?} differ from the ones of the cropped image");
+        }
+    })
+}
+
+#[test]
+fn test_sub_image_contours_are_within_the_region() {
+    let image = get_test_image("art_50x50_dragon").unwrap();
+    let sub_image = image.view(10, 20, 25, 15);
+    let contour_collection = ImageContourCollection::from_sub_image(&sub_image, true, Connectivity::Eight);
+    
+    assert_eq!(contour_collection.origin(), (10, 20));
+    assert_eq!(contour_collection.dimensions(), (25, 15));
+    assert!(contour_collection.all_contours().count() > 0);
+    for contour in contour_collection.all_contours() {
+        for Point2D { x, y, .. } in contour.vertices() {
+            assert!((10..=35).contains(&x) && (20..=35).contains(&y), "({x}, {y}) is outside the region");
+        }
+    }
+}
+
+#[test_case("art_50x50_dragon")]
+#[test_case("text_142x64_theos")]
+#[test_case("pattern_164x164_ga")]
+fn test_sauvola_binarization_of_binary_image(name: &str) {
+    let image = DynamicImage::ImageLuma8(get_test_image(name).unwrap());
+    let binarization = Binarization::Sauvola { window_size: 15, k: 0.34, r: 128.0 };
+    let actual = ImageContourCollection::from_image(&image, binarization, true, Connectivity::Eight);
+    let expected = ImageContourCollection::new(image.as_luma8().unwrap(), true, Connectivity::Eight);
+    assert_eq!(actual.point_list, expected.point_list);
+    assert_eq!(actual.hierarchy, expected.hierarchy);
+}
+
+#[test]
+fn test_net_measurements() {
+    test_all_images(|testcase, image, inverted, _, contour_collection| {
+        let mut expected = Moments::default();
+        for (x, y, &Luma([value])) in image.enumerate_pixels() {
+            if (value != 0) != inverted {
+                let (x, y) = (x as i128, y as i128);
+                expected.m00 += 1;
+                expected.m10 += x;
+                expected.m01 += y;
+                expected.m11 += x * y;
+            }
+        }
+        let actual = contour_collection.outer_contours()
+            .fold(Moments::default(), |moments, contour| moments + contour.net_moments_about(Point2D::zero()));
+        let net_area: i64 = contour_collection.outer_contours().map(|contour| contour.net_area()).sum();
+        
+        let are_equal = actual.m00 == expected.m00 && actual.m10 == expected.m10
+            && actual.m01 == expected.m01 && actual.m11 == expected.m11
+            && net_area as i128 == expected.m00;
+        assert!(are_equal, "{testcase}: net moments differ from the pixel sums");
+    })
+}
+
+#[test]
+fn test_polygon_measurements() {
+    test_all_images(|testcase, _, _, _, contour_collection| {
+        for contour in contour_collection.all_contours() {
+            let polygon = contour.to_polygon::<f64>();
+            let (expected_area, expected_orientation, expected_winding_number) = if contour.is_outer() {
+                (contour.area() as f64, Orientation::Clockwise, 1)
+            } else {
+                (-contour.area() as f64, Orientation::Anticlockwise, -1)
+            };
+            // The first edge goes along the top side of the pixel at the head vertex
+            let (head, next) = contour.even_vertices().next_tuple().unwrap();
+            let head_pixel = head.to_f64() + vec2(0.5 * (next.x - head.x).signum() as f64, 0.5);
+            let moments = contour.raw_moments();
+            let expected_centroid = point2(moments.m10 as f64, moments.m01 as f64) / moments.m00 as f64 + vec2(0.5, 0.5);
+            
+            let is_ok = polygon.signed_area() == expected_area
+                && polygon.orientation() == Some(expected_orientation)
+                && polygon.winding_number(head_pixel) == expected_winding_number
+                && (polygon.centroid().unwrap() - expected_centroid).length() < 1e-6
+                && polygon.bounding_box() == contour.bounding_box().to_f64();
+            assert!(is_ok, "{testcase}: polygon measurements of contour {} are inconsistent", contour.id());
+        }
+    })
+}
+
+#[test_case(|o, (_, h)| o.rotated_clockwise().translated(vec2(h, 0)), imageops::rotate90; "rotated clockwise")]
+#[test_case(|o, (w, _)| o.rotated_anticlockwise().translated(vec2(0, w)), imageops::rotate270; "rotated anticlockwise")]
+#[test_case(|o, (w, h)| o.rotated_180().translated(vec2(w, h)), imageops::rotate180; "rotated 180")]
+#[test_case(|o, (w, _)| o.flipped_horizontally().translated(vec2(w, 0)), imageops::flip_horizontal; "flipped horizontally")]
+#[test_case(|o, (_, h)| o.flipped_vertically().translated(vec2(0, h)), imageops::flip_vertical; "flipped vertically")]
+fn test_orthogonal_transforms(transform: fn(Orthopolygon, (i32, i32)) -> Orthopolygon, transform_image: fn(&GrayImage) -> GrayImage) {
+    test_all_images(|testcase, _, _, _, contour_collection| {
+        let origin = Point2D::from(contour_collection.origin()).to_vector();
         let (width, height) = contour_collection.dimensions();
+        let expected = transform_image(&contour_collection.to_image(false, 1));
+        
+        let mut actual = GrayImage::new(expected.width(), expected.height());
+        let transformed: Vec<_> = contour_collection.all_contours()
+            .map(|contour| {
+                let transformed = transform(contour.translated(-origin), (width, height));
+                assert_eq!(transformed.signed_area(), contour.signed_area(), "{testcase}: orientation is not kept");
+                transformed
+            })
+            .collect();
+        draw_orthopolygons(&mut actual, |_| 255, transformed.iter());
+        assert!(actual == expected, "{testcase}: transformed contours differ from the transformed image");
+    })
+}
+
+#[test]
+fn test_convex_hull() {
+    test_all_images(|testcase, _, _, _, contour_collection| {
+        for contour in contour_collection.all_contours() {
+            let hull = contour.convex_hull();
+            let expected = Polygon::new(contour.vertices()).convex_hull();
+            assert!(hull.vertices().eq(expected.vertices()), "{testcase}: fast hull of contour {} differs", contour.id());
+            
+            let solidity = contour.solidity().unwrap();
+            let rectangularity = contour.rectangularity().unwrap();
+            let rect = contour.min_area_rect().unwrap();
+            let is_ok = 0.0 < solidity && solidity <= 1.0
+                && 0.0 < rectangularity && rectangularity <= 1.0 + 1e-9
+                && rect.area() <= contour.bounding_box().area() as f64 + 1e-9;
+            assert!(is_ok, "{testcase}: hull measurements of contour {} are out of range", contour.id());
+        }
+    })
+}
+
+#[test]
+fn test_despeckling() {
+    test_all_images(|testcase, _, _, connectivity, contour_collection| {
+        for (min_outer_area, min_hole_area) in [(10, 0), (0, 10), (50, 20)] {
+            let actual = contour_collection.despeckled(min_outer_area, min_hole_area);
+            assert_eq!(actual.validate(), Ok(()), "{testcase}");
+            
+            // Trace the rasterized result again
+            let (width, height) = actual.dimensions();
+            let mut canvas = GrayImage::new(width as u32, height as u32);
+            let contours: Vec<_> = actual.all_contours().collect();
+            draw_orthopolygons(&mut canvas, |_| 255, contours.iter());
+            let expected = ImageContourCollection::new(&canvas, false, connectivity);
+            
+            let summarize = |collection: &ImageContourCollection| collection.all_contours()
+                .map(|contour| (contour.is_outer(), contour.area(), contour.even_vertices().collect::<Vec<_>>()))
+                .collect::<Vec<_>>();
+            let actual_contours = summarize(&actual);
+            assert_eq!(actual_contours, summarize(&expected), "{testcase}: despeckled contours differ from the retraced ones");
+            
+            let is_clean = actual_contours.iter().all(|&(is_outer, area, _)|
+                area >= if is_outer { min_outer_area } else { min_hole_area });
+            assert!(is_clean, "{testcase}: small contours left after despeckling");
+        }
+    })
+}
+
+#[test]
+fn test_despeckling_of_small_image() {
+    let image = GrayImage::from_vec(7, 5, vec![
+        1, 1, 1, 1, 1, 0, 1,
+        1, 0, 1, 1, 1, 0, 0,
+        1, 1, 1, 0, 0, 0, 0,
+        1, 1, 1, 0, 1, 1, 0,
+        1, 1, 1, 0, 1, 1, 0,
+    ]).unwrap();
+    let contour_collection = ImageContourCollection::new(&image, false, Connectivity::Eight);
+    let actual = contour_collection.despeckled(2, 2);
+    let expected_image = GrayImage::from_vec(7, 5, vec![
+        1, 1, 1, 1, 1, 0, 0,
+        1, 1, 1, 1, 1, 0, 0,
+        1, 1, 1, 0, 0, 0, 0,
+        1, 1, 1, 0, 1, 1, 0,
+        1, 1, 1, 0, 1, 1, 0,
+    ]).unwrap();
+    let expected = ImageContourCollection::new(&expected_image, false, Connectivity::Eight);
+    
+    let structure = |collection: &ImageContourCollection| collection.hierarchy.iter()
+        .map(|item| (item.parent, item.next_sibling, item.first_child))
+        .collect::<Vec<_>>();
+    assert_eq!(structure(&actual), structure(&expected));
+    let contours = |collection: &ImageContourCollection| collection.all_contours()
+        .map(|contour| contour.even_vertices().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(contours(&actual), contours(&expected));
+}
+
+#[test_case("art_50x50_dragon")]
+#[test_case("text_142x64_theos")]
+#[test_case("pattern_164x164_ga")]
+fn test_spatial_index(name: &str) {
+    let image = get_test_image(name).unwrap();
+    let contour_collection = ImageContourCollection::new(&image, false, Connectivity::Eight);
+    let contours: Vec<_> = contour_collection.all_contours().collect();
+    let depth = |contour: &Contour| iter::successors(contour.parent(), |parent| parent.parent()).count();
+    
+    for cell_size in [1, 7, 1000] {
+        let index = ContourIndex::with_cell_size(&contour_collection, cell_size);
+        
+        for y in -1..=image.height() as i32 + 1 {
+            for x in -1..=image.width() as i32 + 1 {
+                let point = Point2D::new(x, y);
+                let pixel = Box2D::new(point, Point2D::new(x + 1, y + 1));
+                let expected = contours.iter()
+                    .filter(|contour| contour.overlaps_box(pixel))
+                    .max_by_key(|contour| depth(contour))
+                    .map(|contour| contour.id());
+                let actual = index.contour_at(point).map(|contour| contour.id());
+                assert_eq!(actual, expected, "Cell size {cell_size}, point ({x}, {y})");
+            }
+        }
+        
+        for (x, y, width, height) in [(0, 0, 50, 50), (-5, -5, 10, 10), (10, 3, 1, 1), (20, 10, 15, 4), (45, 30, 200, 200)] {
+            let rect = Box2D::new(Point2D::new(x, y), Point2D::new(x + width, y + height));
+            let expected: Vec<_> = contours.iter()
+                .filter(|contour| contour.overlaps_box(rect))
+                .map(|contour| contour.id())
+                .collect();
+            let actual: Vec<_> = index.contours_in_rect(rect).map(|contour| contour.id()).collect();
+            assert_eq!(actual, expected, "Cell size {cell_size}, rect {rect:?}");
+        }
+    }
+}
+
+/// A square with a hole containing a dot:
+/// contour 1 is the square, 2 is the hole, 3 is the dot.
+#[test_case(1, 1 => Some(1); "square")]
+#[test_case(2, 2 => Some(2); "hole")]
+#[test_case(3, 3 => Some(3); "dot")]
+#[test_case(6, 2 => None; "right of the square")]
+#[test_case(2, 6 => None; "below the square")]
+#[test_case(6, 6 => None; "diagonally below the square")]
+#[test_case(-1, -1 => None; "outside the image")]
+fn test_contour_at(x: i32, y: i32) -> Option<usize> {
+    let image = GrayImage::from_vec(7, 7, vec![
+        0, 0, 0, 0, 0, 0, 0,
+        0, 1, 1, 1, 1, 1, 0,
+        0, 1, 0, 0, 0, 1, 0,
+        0, 1, 0, 1, 0, 1, 0,
+        0, 1, 0, 0, 0, 1, 0,
+        0, 1, 1, 1, 1, 1, 0,
+        0, 0, 0, 0, 0, 0, 0,
+    ]).unwrap();
+    let contour_collection = ImageContourCollection::new(&image, false, Connectivity::Eight);
+    contour_collection.spatial_index().contour_at(Point2D::new(x, y)).map(|contour| contour.id())
+}
+
+#[test]
+fn test_label_image() {
+    test_all_images(|testcase, image, inverted, connectivity, contour_collection| {
+        let label_image = contour_collection.to_label_image();
+        assert_eq!(label_image.dimensions(), image.dimensions(), "{testcase}");
+        
+        let label_count = contour_collection.outer_contours().count() as u32;
+        let mut is_used = vec![false; label_count as usize + 1];
+        for (x, y, &Luma([value])) in image.enumerate_pixels() {
+            let Luma([label]) = *label_image.get_pixel(x, y);
+            assert_eq!(label != 0, (value != 0) != inverted, "{testcase}: pixel ({x}, {y})");
+            assert!(label <= label_count, "{testcase}: pixel ({x}, {y})");
+            is_used[label as usize] = true;
+        }
+        assert!(is_used[1..].iter().all(|&u| u), "{testcase}: some labels are not used");
+        
+        // Connected pixels have the same label
+        let neighbors: &[(i32, i32)] = match connectivity {
+            Connectivity::Four => &[(1, 0), (0, 1)],
+            Connectivity::Eight => &[(1, 0), (0, 1), (1, 1), (-1, 1)],
+        };
+        for (x, y, &Luma([label])) in label_image.enumerate_pixels() {
+            for &(dx, dy) in neighbors {
+                let neighbor = x.checked_add_signed(dx)
+                    .and_then(|nx| label_image.get_pixel_checked(nx, y + dy as u32));
+                if let Some(&Luma([neighbor])) = neighbor && label != 0 && neighbor != 0 {
+                    assert_eq!(label, neighbor, "{testcase}: pixel ({x}, {y})");
+                }
+            }
+        }
+    })
+}
+
+#[test]
+fn test_random_access() {
+    test_all_images(|testcase, _, _, _, contour_collection| {
         let contours: Vec<_> = contour_collection.all_contours().collect();
-        let mut canvas = GrayImage::new(width as u32, height as u32);
+        assert_eq!(contour_collection.contour_count(), contours.len(), "{testcase}");
+        let vertex_count: usize = contours.iter().map(|contour| contour.vertices().count()).sum();
+        assert_eq!(contour_collection.vertex_count(), vertex_count, "{testcase}");
+        assert!(contour_collection.contour(0).is_none(), "{testcase}");
+        assert!(contour_collection.contour(contours.len() + 1).is_none(), "{testcase}");
         
-        draw_orthopolygons(&mut canvas, |_| 255, contours.iter());
+        for contour in contours {
+            let found = contour_collection.contour(contour.id()).unwrap();
+            assert_eq!(found.id(), contour.id(), "{testcase}");
+            assert_eq!(found.is_outer(), contour.is_outer(), "{testcase}");
+            assert!(found.vertices().eq(contour.vertices()), "{testcase}");
+            
+            let ancestors: Vec<_> = contour.ancestors().collect();
+            assert_eq!(ancestors.first().map(|ancestor| ancestor.id()), contour.parent().map(|parent| parent.id()), "{testcase}");
+            assert!(ancestors.last().is_none_or(|ancestor| ancestor.parent().is_none()), "{testcase}");
+            assert!(ancestors.iter().all(|ancestor| ancestor.id() < contour.id()), "{testcase}");
+            assert_eq!(contour.depth(), ancestors.len(), "{testcase}");
+            assert_eq!(contour.is_outer(), contour.depth() % 2 == 0, "{testcase}");
+            
+            let siblings: Vec<_> = contour.siblings().map(|sibling| sibling.id()).collect();
+            let expected: Vec<_> = match contour.parent() {
+                Some(parent) => parent.children().map(|child| child.id()).filter(|&id| id != contour.id()).collect(),
+                None => contour_collection.outermost_contours().map(|child| child.id()).filter(|&id| id != contour.id()).collect(),
+            };
+            assert_eq!(siblings, expected, "{testcase}");
+            assert!(contour.siblings().all(|sibling| sibling.is_outer() == contour.is_outer()), "{testcase}");
+        }
+    })
+}
+
+#[test]
+fn test_chain_codes() {
+    test_all_images(|testcase, _, _, _, contour_collection| {
+        for contour in contour_collection.all_contours() {
+            let actual = Orthopolygon::from_freeman_code_4(&contour.freeman_code_4());
+            assert_eq!(actual, Ok(contour.to_orthopolygon()), "{testcase}");
+            let actual = Orthopolygon::from_rd_code(&contour.rd_code());
+            assert_eq!(actual, Ok(contour.to_orthopolygon()), "{testcase}");
+            
+            // The pixel chain can start from another vertex
+            let actual: Vec<_> = Orthopolygon::from_freeman_code_8(&contour.freeman_code_8()).unwrap().even_vertices().collect();
+            let expected: Vec<_> = contour.even_vertices().collect();
+            let shift = actual.iter().position(|&vertex| vertex == expected[0]);
+            assert!(shift.is_some_and(|shift| actual[shift..].iter().chain(&actual[..shift]).eq(&expected)), "{testcase}");
+        }
+    })
+}
+
+#[test]
+fn test_serialization() {
+    test_all_images(|testcase, _, _, _, contour_collection| {
+        let mut data = Vec::new();
+        contour_collection.save(&mut data).unwrap();
+        let loaded = ImageContourCollection::load(data.as_slice()).unwrap();
+        assert_eq!(loaded.origin(), contour_collection.origin(), "{testcase}");
+        assert_eq!(loaded.dimensions(), contour_collection.dimensions(), "{testcase}");
+        assert_eq!(loaded.connectivity(), contour_collection.connectivity(), "{testcase}");
         
-        let mut pixels = canvas.pixels().zip_eq(image.pixels());
-        let are_equal = if inverted {
-            pixels.all(|(&Luma([actual]), &Luma([expected]))| 255 - actual == expected)
-        } else {
-            pixels.all(|(actual, expected)| actual == expected)
+        let contours = |collection: &ImageContourCollection| collection.all_contours()
+            .map(|contour| (contour.id(), contour.is_outer(), contour.vertices().collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        assert_eq!(contours(&loaded), contours(&contour_collection), "{testcase}");
+    })
+}
+
+#[test]
+fn test_serialization_of_sub_image() {
+    let image = get_test_image("art_50x50_dragon").unwrap();
+    let rect = Rect { x: 10, y: 20, width: 25, height: 15 };
+    let contour_collection = ImageContourCollection::new_in_rect(&image, rect, true, Connectivity::Four);
+    let mut data = Vec::new();
+    contour_collection.save(&mut data).unwrap();
+    let loaded = ImageContourCollection::load(data.as_slice()).unwrap();
+    assert_eq!(loaded.origin(), (10, 20));
+    assert_eq!(loaded.validate(), Ok(()));
+    assert!(loaded.all_contours().map(|contour| contour.to_orthopolygon())
+        .eq(contour_collection.all_contours().map(|contour| contour.to_orthopolygon())));
+}
+
+#[test]
+fn test_loading_of_corrupt_data() {
+    let image = get_test_image("art_50x50_dragon").unwrap();
+    let contour_collection = ImageContourCollection::new(&image, false, Connectivity::Eight);
+    let mut data = Vec::new();
+    contour_collection.save(&mut data).unwrap();
+    
+    for length in 0..data.len() {
+        let result = ImageContourCollection::load(&data[..length]);
+        assert!(matches!(result, Err(LoadError::Truncated)), "Length {length}");
+    }
+    assert!(matches!(ImageContourCollection::load(&b"PNG\0\0\0"[..]), Err(LoadError::UnknownFormat)));
+    let mut future = data.clone();
+    future[4] = 2;
+    assert!(matches!(ImageContourCollection::load(future.as_slice()), Err(LoadError::UnsupportedVersion { version: 2 })));
+    let mut extended = data.clone();
+    extended.push(0);
+    assert!(matches!(ImageContourCollection::load(extended.as_slice()), Err(LoadError::TrailingData)));
+    
+    // Well-formed data describing a dot outside the hole containing it
+    let image = GrayImage::from_vec(5, 5, vec![
+        1, 1, 1, 1, 1,
+        1, 0, 0, 0, 1,
+        1, 0, 1, 0, 1,
+        1, 0, 0, 0, 1,
+        1, 1, 1, 1, 1,
+    ]).unwrap();
+    let mut inconsistent = ImageContourCollection::new(&image, false, Connectivity::Eight);
+    inconsistent.point_list[2..4].iter_mut().for_each(|p| p.x += 2);
+    let mut inconsistent_data = Vec::new();
+    inconsistent.save(&mut inconsistent_data).unwrap();
+    assert!(matches!(ImageContourCollection::load(inconsistent_data.as_slice()), Err(LoadError::Corrupt { .. })));
+    
+    // Corrupt bytes should not cause a panic, and the result should be safe to iterate
+    for index in 5..data.len() {
+        for value in [0x00, 0x7F, 0x80, 0xFF] {
+            let mut corrupt = data.clone();
+            corrupt[index] = value;
+            if let Ok(loaded) = ImageContourCollection::load(corrupt.as_slice()) {
+                for contour in loaded.all_contours() {
+                    assert!(contour.vertices().count() >= 4);
+                }
+            }
+        }
+    }
+}
+
+#[test_case("art_50x50_dragon", 1)]
+#[test_case("text_142x64_theos", 1)]
+#[test_case("text_142x64_theos", 2)]
+#[test_case("pattern_164x164_ga", 3)]
+fn test_morphology(name: &str, radius: u32) {
+    let image = get_test_image(name).unwrap();
+    for connectivity in [Connectivity::Eight, Connectivity::Four] {
+        let contour_collection = ImageContourCollection::new(&image, true, connectivity);
+        let r = radius as i64;
+        
+        // Pixel by pixel, the pixels outside the image being background
+        let is_foreground = |image: &GrayImage, x: i64, y: i64| x >= 0 && y >= 0
+            && image.get_pixel_checked(x as u32, y as u32).is_some_and(|&Luma([value])| value == 0);
+        let morph = |image: &GrayImage, is_dilation: bool| {
+            let (offset, margin) = if is_dilation { (-r, 2 * radius) } else { (0, 0) };
+            GrayImage::from_fn(image.width() + margin, image.height() + margin, |x, y| {
+                let (x, y) = (x as i64 + offset, y as i64 + offset);
+                let mut window = (-r..=r).flat_map(|dy| (-r..=r).map(move |dx| (x + dx, y + dy)));
+                let is_set = if is_dilation {
+                    window.any(|(x, y)| is_foreground(image, x, y))
+                } else {
+                    window.all(|(x, y)| is_foreground(image, x, y))
+                };
+                Luma([if is_set { 0 } else { 255 }])
+            })
         };
+        let crop = |image: &GrayImage| image::imageops::crop_imm(image, radius, radius, image.width() - 2 * radius, image.height() - 2 * radius).to_image();
         
-        assert!(are_equal, "{testcase}: rasterized contours differ from the original image");
+        let dilated = contour_collection.dilated(radius);
+        assert_eq!(dilated.origin(), (-(radius as i32), -(radius as i32)));
+        assert_eq!(dilated.to_image(true, 1), morph(&image, true), "{name}, {connectivity:?}: dilation");
+        let eroded = contour_collection.eroded(radius);
+        assert_eq!(eroded.to_image(true, 1), morph(&image, false), "{name}, {connectivity:?}: erosion");
+        let opened = contour_collection.opened(radius);
+        assert_eq!(opened.to_image(true, 1), crop(&morph(&morph(&image, false), true)), "{name}, {connectivity:?}: opening");
+        let closed = contour_collection.closed(radius);
+        assert_eq!(closed.to_image(true, 1), crop(&morph(&morph(&image, true), false)), "{name}, {connectivity:?}: closing");
+        
+        // The same contours, but the traced image bounds are the bounding box of the result
+        let contours: Vec<_> = contour_collection.all_contours().collect();
+        let from_contours = ImageContourCollection::from_morphological_operation(MorphologicalOperation::Dilation, contours.iter(), radius, connectivity);
+        assert!(from_contours.all_contours().map(|contour| contour.to_orthopolygon())
+            .eq(dilated.all_contours().map(|contour| contour.to_orthopolygon())), "{name}, {connectivity:?}");
+        
+        for collection in [dilated, eroded, opened, closed, from_contours] {
+            assert_eq!(collection.validate(), Ok(()), "{name}, {connectivity:?}");
+            assert_eq!(collection.connectivity(), connectivity);
+        }
+    }
+}
+
+#[test]
+fn test_morphology_with_too_large_radius() {
+    let image = get_test_image("art_50x50_dragon").unwrap();
+    let contour_collection = ImageContourCollection::new(&image, true, Connectivity::Eight);
+    let radius = i32::MAX as u32 + 1;
+    let expected_size = 2 * radius as u64 + 50;
+    assert_eq!(contour_collection.try_dilated(radius).err(), Some(BuildError::TooLarge { width: expected_size, height: expected_size }));
+    assert!(matches!(contour_collection.try_closed(radius), Err(BuildError::TooLarge { .. })));
+    assert_eq!(contour_collection.eroded(radius).contour_count(), 0);
+    assert_eq!(contour_collection.opened(radius).contour_count(), 0);
+}
+
+#[test]
+fn test_from_orthopolygons() {
+    let image = get_test_image("art_50x50_dragon").unwrap();
+    let contour_collection = ImageContourCollection::new(&image, false, Connectivity::Eight);
+    let contours: Vec<_> = contour_collection.all_contours().collect();
+    let bounds = Box2D::new(Point2D::new(10, 5), Point2D::new(40, 30));
+    let actual = ImageContourCollection::from_orthopolygons(contours.iter(), bounds, Connectivity::Eight);
+    let expected = ImageContourCollection::new_in_rect(&image, Rect { x: 10, y: 5, width: 30, height: 25 }, false, Connectivity::Eight);
+    assert!(actual.all_contours().map(|contour| contour.to_orthopolygon())
+        .eq(expected.all_contours().map(|contour| contour.to_orthopolygon())));
+}
+
+#[test_case(BooleanOperation::Union)]
+#[test_case(BooleanOperation::Intersection)]
+#[test_case(BooleanOperation::Difference)]
+#[test_case(BooleanOperation::SymmetricDifference)]
+fn test_boolean_operations(operation: BooleanOperation) {
+    for name in ["art_50x50_dragon", "text_142x64_theos"] {
+        let image = get_test_image(name).unwrap();
+        let contour_collection = ImageContourCollection::new(&image, true, Connectivity::Eight);
+        let a: Vec<_> = contour_collection.all_contours().collect();
+        let shift = vec2(3, -2);
+        let b: Vec<_> = a.iter().map(|contour| Orthopolygon::new(contour.even_vertices().map(|p| p + shift))).collect();
+        
+        let result = ImageContourCollection::from_boolean_operation(operation, a.iter(), b.iter(), Connectivity::Eight);
+        assert_eq!(result.validate(), Ok(()), "{name}");
+        let (left, top) = result.origin();
+        let canvas = result.to_image(true, 1);
+        
+        let is_foreground = |image: &GrayImage, x: i32, y: i32| x >= 0 && y >= 0
+            && image.get_pixel_checked(x as u32, y as u32).is_some_and(|&Luma([value])| value == 0);
+        for y in -5..image.height() as i32 + 5 {
+            for x in -5..image.width() as i32 + 5 {
+                let expected = operation.apply(is_foreground(&image, x, y), is_foreground(&image, x - shift.x, y - shift.y));
+                let actual = is_foreground(&canvas, x - left, y - top);
+                assert_eq!(actual, expected, "{name}: pixel ({x}, {y})");
+            }
+        }
+    }
+}
+
+#[test]
+fn test_validation() {
+    test_all_images(|testcase, _, _, _, contour_collection| {
+        assert_eq!(contour_collection.validate(), Ok(()), "{testcase}");
     })
 }
 
-fn test_all_images(test: impl Fn(String, &GrayImage, bool, ImageContourCollection)) {
-    for (name, image) in get_test_images() {
+#[test]
+fn test_validation_of_sub_image() {
+    let image = get_test_image("art_50x50_dragon").unwrap();
+    let rect = Rect { x: 10, y: 20, width: 25, height: 15 };
+    let contour_collection = ImageContourCollection::new_in_rect(&image, rect, true, Connectivity::Four);
+    assert_eq!(contour_collection.validate(), Ok(()));
+}
+
+/// Image with a hole containing a dot:
+/// contour 1 is the outer one, 2 is the hole, 3 is the dot.
+#[test_case(|c| c.hierarchy.clear() => Err(ValidationError::MissingRoot))]
+#[test_case(|c| c.hierarchy[1].first_child = NonZeroUsize::new(9) => Err(ValidationError::LinkOutOfRange { contour: 1 }))]
+#[test_case(|c| c.hierarchy[3].first_child = NonZeroUsize::new(2) => Err(ValidationError::Cycle { contour: 2 }))]
+#[test_case(|c| c.hierarchy[3].parent = 1 => Err(ValidationError::IncorrectParent { contour: 3 }))]
+#[test_case(|c| c.hierarchy[2].first_child = None => Err(ValidationError::Unreachable { contour: 3 }))]
+#[test_case(|c| c.point_list[5].next = 99 => Err(ValidationError::PointOutOfRange { contour: 1 }))]
+#[test_case(|c| c.point_list[5].next = 5 => Err(ValidationError::OpenContour { contour: 1 }))]
+#[test_case(|c| c.point_list[0].next = 0 => Err(ValidationError::Degenerate { contour: 1 }))]
+#[test_case(|c| c.point_list[2..4].iter_mut().for_each(|p| p.x += 2) => Err(ValidationError::OutsideParent { contour: 3 }))]
+#[test_case(|c| c.point_list.iter_mut().for_each(|p| p.y -= 1) => Err(ValidationError::OutsideImage { contour: 1 }))]
+#[test_case(|_| () => Ok(()))]
+fn test_validation_errors(corrupt: fn(&mut ImageContourCollection)) -> Result<(), ValidationError> {
+    let image = GrayImage::from_vec(5, 5, vec![
+        1, 1, 1, 1, 1,
+        1, 0, 0, 0, 1,
+        1, 0, 1, 0, 1,
+        1, 0, 0, 0, 1,
+        1, 1, 1, 1, 1,
+    ]).unwrap();
+    let mut contour_collection = ImageContourCollection::new(&image, false, Connectivity::Eight);
+    corrupt(&mut contour_collection);
+    contour_collection.validate()
+}
+
+#[test]
+fn test_build_errors() {
+    let image = GrayImage::new(8, 4);
+    let rect = Rect { x: 6, y: 0, width: 3, height: 4 };
+    assert_eq!(ImageContourCollection::try_new_in_rect(&image, rect, false, Connectivity::Eight).err(),
+        Some(BuildError::RectOutOfBounds { rect }));
+    
+    let buffer = [0; 7];
+    assert_eq!(ImageContourCollection::try_from_packed(&buffer, 9, 4, 1, false, Connectivity::Eight).err(),
+        Some(BuildError::StrideTooSmall { stride: 1, row_length: 2 }));
+    assert_eq!(ImageContourCollection::try_from_packed(&buffer, 9, 4, 2, false, Connectivity::Eight).err(),
+        Some(BuildError::BufferTooShort { length: 7, expected_length: 8 }));
+    
+    let width = i32::MAX as u32 + 1;
+    assert_eq!(ImageContourCollection::from_row_changes(width, [[0, 1]], Connectivity::Eight).err(),
+        Some(BuildError::TooLarge { width: width as u64, height: 0 }));
+    
+    let left = Orthopolygon::from(vec![point2(-10, 0), point2(0, 1)]);
+    let right = Orthopolygon::from(vec![point2(i32::MAX - 10, 0), point2(i32::MAX, 1)]);
+    let result = ImageContourCollection::try_from_boolean_operation(BooleanOperation::Union, [left].iter(), [right].iter(), Connectivity::Eight);
+    assert_eq!(result.err(), Some(BuildError::TooLarge { width: i32::MAX as u64 + 10, height: 1 }));
+}
+
+fn test_all_images(test: impl Fn(String, &GrayImage, bool, Connectivity, ImageContourCollection)) {
+    for (name, image) in get_test_images().unwrap() {
+        let image = image.unwrap();
         for &inverted in [false, true].iter() {
-            let contour_collection = ImageContourCollection::new(&image, inverted);
-            let testcase = if inverted {
-                format!("Image: '{name}', inverted")
-            } else {
-                format!("Image: '{name}'")
-            };
-            test(testcase, &image, inverted, contour_collection);
+            for &connectivity in [Connectivity::Eight, Connectivity::Four].iter() {
+                let contour_collection = ImageContourCollection::new(&image, inverted, connectivity);
+                let testcase = if inverted {
+                    format!("Image: '{name}', inverted, {connectivity:?}-connected")
+                } else {
+                    format!("Image: '{name}', {connectivity:?}-connected")
+                };
+                test(testcase, &image, inverted, connectivity, contour_collection);
+            }
         }
     }
 }
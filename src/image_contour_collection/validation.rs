@@ -0,0 +1,105 @@
+use euclid::default::Point2D;
+use crate::geometry::{Orthopolygonlike, PointPosition, Polygonlike};
+use super::{ImageContourCollection, Connectivity, ValidationError};
+
+/// Checks the hierarchy tree and the point links first,
+/// so that contours can be safely iterated when checking their nesting.
+pub fn validate(collection: &ImageContourCollection) -> Result<(), ValidationError> {
+    validate_hierarchy(collection)?;
+    validate_point_list(collection)?;
+    validate_nesting(collection)
+}
+
+/// Every item should be reachable from the root exactly once,
+/// and be linked from its parent or from a sibling with the same parent.
+fn validate_hierarchy(collection: &ImageContourCollection) -> Result<(), ValidationError> {
+    let h = &collection.hierarchy;
+    if h.is_empty() {
+        return Err(ValidationError::MissingRoot);
+    }
+    let mut is_visited = vec![false; h.len()];
+    is_visited[0] = true;
+    
+    let mut stack = vec![0];
+    while let Some(index) = stack.pop() {
+        let current = &h[index];
+        
+        let links = [(current.first_child, index), (current.next_sibling, current.parent)];
+        for (link, expected_parent) in links {
+            if let Some(contour) = link.map(|link| link.get()) {
+                if contour >= h.len() {
+                    return Err(ValidationError::LinkOutOfRange { contour: index });
+                }
+                if is_visited[contour] {
+                    return Err(ValidationError::Cycle { contour });
+                }
+                if h[contour].parent != expected_parent {
+                    return Err(ValidationError::IncorrectParent { contour });
+                }
+                is_visited[contour] = true;
+                stack.push(contour);
+            }
+        }
+    }
+    
+    match is_visited.iter().position(|&v| !v) {
+        Some(contour) => Err(ValidationError::Unreachable { contour }),
+        None => Ok(()),
+    }
+}
+
+/// Following the point links from the head of each contour should lead back to the head.
+fn validate_point_list(collection: &ImageContourCollection) -> Result<(), ValidationError> {
+    let point_list = &collection.point_list;
+    for (contour, item) in collection.hierarchy.iter().enumerate().skip(1) {
+        let head = item.head_point_index;
+        let mut current = head;
+        let mut count = 0;
+        loop {
+            if current >= point_list.len() {
+                return Err(ValidationError::PointOutOfRange { contour });
+            }
+            count += 1;
+            if count > point_list.len() {
+                return Err(ValidationError::OpenContour { contour });
+            }
+            current = point_list[current].next;
+            if current == head {
+                break;
+            }
+        }
+        if count < 2 {
+            return Err(ValidationError::Degenerate { contour });
+        }
+    }
+    Ok(())
+}
+
+/// Each contour should lie inside its parent, touching it only at vertices
+/// where the connectivity allows that.
+/// The outermost contours should lie within the image bounds.
+fn validate_nesting(collection: &ImageContourCollection) -> Result<(), ValidationError> {
+    let (left, top) = collection.origin();
+    let (width, height) = collection.dimensions();
+    
+    for contour in collection.all_contours() {
+        if let Some(parent) = contour.parent() {
+            let can_touch = parent.is_outer() == (collection.connectivity == Connectivity::Eight);
+            let is_inside = contour.vertices().all(|point| match parent.get_point_position(point) {
+                PointPosition::Inside => true,
+                PointPosition::Vertex => can_touch,
+                PointPosition::Outside | PointPosition::Edge => false,
+            });
+            if !is_inside {
+                return Err(ValidationError::OutsideParent { contour: contour.id() });
+            }
+        } else {
+            let is_inside = contour.vertices().all(|Point2D { x, y, .. }|
+                left <= x && x <= left + width && top <= y && y <= top + height);
+            if !is_inside {
+                return Err(ValidationError::OutsideImage { contour: contour.id() });
+            }
+        }
+    }
+    Ok(())
+}
@@ -10,7 +10,7 @@ mod approximation;
 use std::{fs, time::Duration};
 use std::time::Instant;
 use book::Book;
-use image_contour_collection::ImageContourCollection;
+use image_contour_collection::{ImageContourCollection, Connectivity};
 use silly_svg::{write_contour_collection_as_svg_file, write_book_as_multiple_svg_files};
 use test_images::{get_test_images, get_test_image};
 use approximation::to_accurate_polygon;
@@ -20,24 +20,26 @@ type Error = Box<dyn std::error::Error>;
 fn main() -> Result<(), Error> {
     // std::env::set_var("RUST_BACKTRACE", "1");
     
-    process_test_images();
-    // measure_performance("noise_200x100_white", true, 1000);
-    // measure_performance("text_5012x7060_math", true, 100);
-    // measure_performance("text_7717x10672_gospel", true, 50);
+    process_test_images()?;
+    // measure_performance("noise_200x100_white", true, 1000)?;
+    // measure_performance("text_5012x7060_math", true, 100)?;
+    // measure_performance("text_7717x10672_gospel", true, 50)?;
     
-    // process_ku();
+    // process_ku()?;
     
     Ok(())
 }
 
-fn process_ku() {
+fn process_ku() -> Result<(), Error> {
     let start_decoding = Instant::now();
-    let images: Vec<_> = (1..=402).map(|i| get_test_image(&format!("ku/{i:03}"))).collect();
+    let images = (1..=402).map(|i| get_test_image(&format!("ku/{i:03}"))).collect::<Result<Vec<_>, _>>()?;
     let decoding = start_decoding.elapsed();
     println!("Decoding:   {:.3} s", decoding.as_secs_f64());
     
     let start_contouring = Instant::now();
-    let contours: Vec<_> = images.iter().map(ImageContourCollection::black_on_white).collect();
+    let contours = images.iter()
+        .map(|image| ImageContourCollection::try_new(image, true, Connectivity::Eight))
+        .collect::<Result<Vec<_>, _>>()?;
     let contouring = start_contouring.elapsed();
     println!("Contouring: {:.3} s", contouring.as_secs_f64());
     
@@ -50,17 +52,18 @@ fn process_ku() {
     write_book_as_multiple_svg_files(&book);
     let writing = start_writing.elapsed();
     println!("Writing:    {:.3} s", writing.as_secs_f64());
+    Ok(())
 }
 
-fn process_test_images() {
-    fs::create_dir_all("output").unwrap();
+fn process_test_images() -> Result<(), Error> {
+    fs::create_dir_all("output")?;
     println!("Processing test images:");
     let inverted = true;
     
     let mut time = Duration::ZERO;
-    for (name, image) in get_test_images() {
+    for (name, image) in get_test_images()? {
         println!("- {name}");
-        let contour_collection = ImageContourCollection::new(&image, inverted);
+        let contour_collection = ImageContourCollection::try_new(&image?, inverted, Connectivity::Eight)?;
         let start = Instant::now();
         let approximation: Vec<_> = contour_collection.all_contours().map(|c| to_accurate_polygon(&c)).collect();
         time += start.elapsed();
@@ -68,18 +71,20 @@ fn process_test_images() {
     }
     println!("{:.3} ms", time.as_secs_f64() * 1000.0);
     println!("");
+    Ok(())
 }
 
-fn measure_performance(name: &str, inverted: bool, iterations: usize) {
-    let image = get_test_image(name);
+fn measure_performance(name: &str, inverted: bool, iterations: usize) -> Result<(), Error> {
+    let image = get_test_image(name)?;
     println!("Measuring performance on '{name}'...");
     
     let start = Instant::now();
     for _ in 0..iterations {
-        let _ = ImageContourCollection::new(&image, inverted);
+        let _ = ImageContourCollection::new(&image, inverted, Connectivity::Eight);
     }
     let time = start.elapsed();
     
     let per_iteration = time.as_secs_f64() * 1000.0 / iterations as f64;
     println!("{per_iteration:.3} ms");
+    Ok(())
 }
\ No newline at end of file
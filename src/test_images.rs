@@ -1,38 +1,39 @@
-use std::{fs, path::{Path, PathBuf}};
-use image::{GrayImage, ImageReader};
-use itertools::Itertools;
-
-pub fn get_test_images() -> impl Iterator<Item = (String, GrayImage)> {
-    let files: Vec<_> = fs::read_dir(TEST_IMAGES_DIRECTORY).unwrap()
-        .map(|file| file.unwrap())
-        .filter(|file| file.file_type().unwrap().is_file())
-        .map(|file| {
+use std::{fs, path::Path};
+use image::{GrayImage, ImageReader, ImageResult};
+
+/// Loads the test images sorted by name.
+/// An error is returned for each file that cannot be read or decoded.
+pub fn get_test_images() -> ImageResult<impl Iterator<Item = (String, ImageResult<GrayImage>)>> {
+    let mut files = Vec::new();
+    for file in fs::read_dir(TEST_IMAGES_DIRECTORY)? {
+        let file = file?;
+        if file.file_type()?.is_file() {
             let path = file.path();
-            let name: String = path.file_stem().unwrap_or(path.file_name().unwrap()).to_os_string().into_string().unwrap();
-            (path, name)
-        })
-        .sorted_by_key(|(_, name)| name.clone())
-        .collect();
+            let name = path.file_stem().unwrap_or(path.as_os_str()).to_string_lossy().into_owned();
+            files.push((path, name));
+        }
+    }
+    files.sort_by_key(|(_, name)| name.clone());
     
     let use_whitelist = files.iter().any(|(_, name)| is_in_whitelist(name));
     let condition = if use_whitelist { is_in_whitelist } else { is_not_in_blacklist };
     
-    files.into_iter()
+    Ok(files.into_iter()
         .filter(move |(_, name)| condition(name))
         .map(|(path, name)| {
             let image = load_image(&path);
-            (name.clone(), image)
-        })
+            (name, image)
+        }))
 }
 
-pub fn get_test_image(name: &str) -> GrayImage {
+pub fn get_test_image(name: &str) -> ImageResult<GrayImage> {
     let path = Path::new(TEST_IMAGES_DIRECTORY).join(format!("{name}.png"));
     load_image(&path)
 }
 
-fn load_image(path: &PathBuf) -> GrayImage {
-    let image = ImageReader::open(path).unwrap().decode().unwrap();
-    image.into_luma8()
+fn load_image(path: &Path) -> ImageResult<GrayImage> {
+    let image = ImageReader::open(path)?.decode()?;
+    Ok(image.into_luma8())
 }
 
 fn is_in_whitelist(name: &str) -> bool {
@@ -55,7 +56,8 @@ mod tests {
 
     #[test]
     fn images_are_binarized() {
-        for (name, image) in get_test_images() {
+        for (name, image) in get_test_images().unwrap() {
+            let image = image.unwrap();
             let is_binarized = image.pixels().all(|&Luma([value])| value == 0 || value == 255);
             assert!(is_binarized, "Image '{name}' is not binarized");
         }